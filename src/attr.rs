@@ -0,0 +1,197 @@
+use crate::{Arsc, ResourceId, ResourceValue, Value};
+
+/// AttrFormat describes which kinds of values an attribute accepts
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct AttrFormat(pub u32);
+
+impl AttrFormat {
+    pub const ANY: AttrFormat = AttrFormat(0x0000_FFFF);
+    pub const REFERENCE: AttrFormat = AttrFormat(1 << 0);
+    pub const STRING: AttrFormat = AttrFormat(1 << 1);
+    pub const INTEGER: AttrFormat = AttrFormat(1 << 2);
+    pub const BOOLEAN: AttrFormat = AttrFormat(1 << 3);
+    pub const COLOR: AttrFormat = AttrFormat(1 << 4);
+    pub const FLOAT: AttrFormat = AttrFormat(1 << 5);
+    pub const DIMENSION: AttrFormat = AttrFormat(1 << 6);
+    pub const FRACTION: AttrFormat = AttrFormat(1 << 7);
+    /// The value is one of the symbols of the attribute
+    pub const ENUM: AttrFormat = AttrFormat(1 << 16);
+    /// The value is a combination of the symbols of the attribute
+    pub const FLAGS: AttrFormat = AttrFormat(1 << 17);
+
+    /// return true if all the flags in `other` are also set in `self`
+    pub fn contains(self, other: AttrFormat) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for AttrFormat {
+    type Output = AttrFormat;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        AttrFormat(self.0 | rhs.0)
+    }
+}
+
+/// AttrSymbol is a named value of an enum or flags attribute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrSymbol {
+    /// the id of the `id` resource that names the symbol
+    pub id: ResourceId,
+    /// the name of the symbol, or None if its id is not in the arsc
+    pub name: Option<String>,
+    pub value: u32,
+}
+
+/// AttrDefinition is the decoded bag of an `attr` resource
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttrDefinition {
+    pub format: AttrFormat,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    /// whether the values of the attribute should be localized, see `L10N_SUGGESTED`
+    pub l10n: Option<u32>,
+    /// the enum or flag symbols, in the order of the bag
+    pub symbols: Vec<AttrSymbol>,
+}
+
+impl AttrDefinition {
+    /// Key of the bag value holding the format of the attribute
+    pub const ATTR_TYPE: ResourceId = ResourceId(0x0100_0000);
+    /// Key of the bag value holding the minimum of an integer attribute
+    pub const ATTR_MIN: ResourceId = ResourceId(0x0100_0001);
+    /// Key of the bag value holding the maximum of an integer attribute
+    pub const ATTR_MAX: ResourceId = ResourceId(0x0100_0002);
+    /// Key of the bag value holding the localization requirement
+    pub const ATTR_L10N: ResourceId = ResourceId(0x0100_0003);
+
+    pub const L10N_NOT_REQUIRED: u32 = 0;
+    pub const L10N_SUGGESTED: u32 = 1;
+
+    /// Decode the values of an `attr` bag. The names of the symbols are left as None
+    pub fn from_bag(values: &[(ResourceId, Value)]) -> Self {
+        let mut definition = AttrDefinition::default();
+        for (key, value) in values {
+            match *key {
+                Self::ATTR_TYPE => definition.format = AttrFormat(value.data_index as u32),
+                Self::ATTR_MIN => definition.min = Some(value.data_index as i32),
+                Self::ATTR_MAX => definition.max = Some(value.data_index as i32),
+                Self::ATTR_L10N => definition.l10n = Some(value.data_index as u32),
+                id => definition.symbols.push(AttrSymbol {
+                    id,
+                    name: None,
+                    value: value.data_index as u32,
+                }),
+            }
+        }
+        definition
+    }
+
+    /// Turn an integer value of an enum or flags attribute into its symbolic form,
+    /// e.g. `center_vertical|start`
+    ///
+    /// # Argument:
+    /// * value - the data of the value
+    /// # Returns:
+    /// the symbolic form, or None if the value cannot be represented by the named symbols
+    pub fn format_value(&self, value: u32) -> Option<String> {
+        let named_symbols = || {
+            self.symbols
+                .iter()
+                .filter_map(|symbol| Some((symbol.name.as_deref()?, symbol.value)))
+        };
+        if self.format.contains(AttrFormat::FLAGS) {
+            if value == 0 {
+                return named_symbols()
+                    .find(|(_, flags)| *flags == 0)
+                    .map(|(name, _)| name.to_string());
+            }
+            // greedily pick the symbol adding the most bits, and among them the one with
+            // the fewest bits, so that 0x800013 is `center_vertical|start` rather than `start|center`
+            let candidates = named_symbols()
+                .filter(|(_, flags)| *flags != 0 && value & flags == *flags)
+                .collect::<Vec<_>>();
+            let mut picked = vec![false; candidates.len()];
+            let mut covered = 0;
+            while covered != value {
+                let next = candidates
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, flags))| flags & !covered != 0)
+                    .max_by_key(|(_, (_, flags))| {
+                        (
+                            (flags & !covered).count_ones(),
+                            std::cmp::Reverse(flags.count_ones()),
+                        )
+                    });
+                match next {
+                    Some((index, (_, flags))) => {
+                        picked[index] = true;
+                        covered |= flags;
+                    }
+                    None => return None,
+                }
+            }
+            let names = candidates
+                .iter()
+                .zip(picked)
+                .filter(|(_, picked)| *picked)
+                .map(|((name, _), _)| *name)
+                .collect::<Vec<_>>();
+            Some(names.join("|"))
+        } else if self.format.contains(AttrFormat::ENUM) {
+            named_symbols()
+                .find(|(_, symbol_value)| *symbol_value == value)
+                .map(|(name, _)| name.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Turn the symbolic form of a value back into the integer value
+    ///
+    /// # Argument:
+    /// * text - a symbol name, or symbol names separated by `|` for flags
+    /// # Returns:
+    /// the integer value, or None if a name is not a symbol of this attribute
+    pub fn parse_value(&self, text: &str) -> Option<u32> {
+        let symbol_value = |name: &str| {
+            self.symbols
+                .iter()
+                .find(|symbol| symbol.name.as_deref() == Some(name.trim()))
+                .map(|symbol| symbol.value)
+        };
+        if self.format.contains(AttrFormat::FLAGS) {
+            text.split('|')
+                .try_fold(0, |value, name| Some(value | symbol_value(name)?))
+        } else if self.format.contains(AttrFormat::ENUM) {
+            symbol_value(text)
+        } else {
+            None
+        }
+    }
+}
+
+impl Arsc {
+    /// Decode the definition of an `attr` resource, with the names of its symbols
+    ///
+    /// # Argument:
+    /// * res_id - the id of the attribute
+    /// # Returns:
+    /// the definition, or None if the resource cannot be found or it is not a bag
+    pub fn attr_definition(&self, res_id: ResourceId) -> Option<AttrDefinition> {
+        let view = self.get(res_id)?;
+        let values = view
+            .entries
+            .iter()
+            .find_map(|(_, entry)| match &entry.value {
+                ResourceValue::Bag { values, .. } => Some(values),
+                ResourceValue::Plain(_) => None,
+            })?;
+        let mut definition = AttrDefinition::from_bag(values);
+        for symbol in &mut definition.symbols {
+            symbol.name = self.get(symbol.id).map(|view| view.key_name.to_string());
+        }
+        Some(definition)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{ResourceId, ResourceValue, Value};
+use std::collections::BTreeMap;
+
+/// The key of the first item of an array bag. The key of the n-th item is `ARRAY_START + n`
+const ARRAY_START: u32 = 0x0200_0000;
+
+/// Quantity is the plural category that a value in a `plurals` bag is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Quantity {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl Quantity {
+    /// return the key of the quantity in a `plurals` bag
+    pub fn key(&self) -> ResourceId {
+        ResourceId(match self {
+            Quantity::Other => 0x0100_0004,
+            Quantity::Zero => 0x0100_0005,
+            Quantity::One => 0x0100_0006,
+            Quantity::Two => 0x0100_0007,
+            Quantity::Few => 0x0100_0008,
+            Quantity::Many => 0x0100_0009,
+        })
+    }
+
+    /// return the quantity of a key in a `plurals` bag, or None if it is not a quantity key
+    pub fn from_key(key: ResourceId) -> Option<Self> {
+        let quantity = match key.0 {
+            0x0100_0004 => Quantity::Other,
+            0x0100_0005 => Quantity::Zero,
+            0x0100_0006 => Quantity::One,
+            0x0100_0007 => Quantity::Two,
+            0x0100_0008 => Quantity::Few,
+            0x0100_0009 => Quantity::Many,
+            _ => return None,
+        };
+        Some(quantity)
+    }
+
+    /// return the name of the quantity used in resource xml, e.g. `few`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quantity::Zero => "zero",
+            Quantity::One => "one",
+            Quantity::Two => "two",
+            Quantity::Few => "few",
+            Quantity::Many => "many",
+            Quantity::Other => "other",
+        }
+    }
+}
+
+impl ResourceValue {
+    /// Read the bag of an `array`, `string-array` or `integer-array` resource
+    ///
+    /// # Returns:
+    /// the items ordered by their indices, or None if it is not a bag of array items
+    pub fn as_array(&self) -> Option<Vec<&Value>> {
+        let values = match self {
+            ResourceValue::Bag { values, .. } => values,
+            ResourceValue::Plain(_) => return None,
+        };
+        let mut items = values
+            .iter()
+            .map(|(key, value)| Some((key.0.checked_sub(ARRAY_START)?, value)))
+            .collect::<Option<Vec<_>>>()?;
+        if items.iter().any(|(index, _)| *index > u16::MAX as u32) {
+            return None;
+        }
+        items.sort_by_key(|(index, _)| *index);
+        Some(items.into_iter().map(|(_, value)| value).collect())
+    }
+
+    /// Read the bag of a `plurals` resource
+    ///
+    /// # Returns:
+    /// the value of every quantity, or None if it is not a bag of quantities
+    pub fn as_plurals(&self) -> Option<BTreeMap<Quantity, &Value>> {
+        match self {
+            ResourceValue::Bag { values, .. } => values
+                .iter()
+                .map(|(key, value)| Some((Quantity::from_key(*key)?, value)))
+                .collect(),
+            ResourceValue::Plain(_) => None,
+        }
+    }
+
+    /// Create the bag of an array resource
+    ///
+    /// Notice: the entry holding a bag must be flagged as complex
+    ///
+    /// # Argument:
+    /// * items - the items of the array, in order
+    pub fn array<I: IntoIterator<Item = Value>>(items: I) -> Self {
+        ResourceValue::Bag {
+            parent: ResourceId(0),
+            values: items
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| (ResourceId(ARRAY_START + index as u32), value))
+                .collect(),
+        }
+    }
+
+    /// Create the bag of a `plurals` resource
+    ///
+    /// Notice: the entry holding a bag must be flagged as complex
+    ///
+    /// # Argument:
+    /// * quantities - the value of every quantity
+    pub fn plurals<I: IntoIterator<Item = (Quantity, Value)>>(quantities: I) -> Self {
+        let quantities = quantities.into_iter().collect::<BTreeMap<_, _>>();
+        ResourceValue::Bag {
+            parent: ResourceId(0),
+            values: quantities
+                .into_iter()
+                .map(|(quantity, value)| (quantity.key(), value))
+                .collect(),
+        }
+    }
+}
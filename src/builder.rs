@@ -0,0 +1,409 @@
+use crate::{
+    Arsc, Config, Dimension, Error, Package, ResTableConfig, ResourceEntry, ResourceId,
+    ResourceValue, Resources, Result, Spec, Specs, StringPool, Type, Value, ValueData,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// ArscBuilder constructs a resource table from scratch.
+/// Strings values of all the packages are collected in the global string pool
+///
+/// # Example
+/// ```rust
+/// use arsc::{ArscBuilder, ResTableConfig};
+///
+/// # fn main() -> arsc::Result<()> {
+/// let mut builder = ArscBuilder::new();
+/// let package = builder.package(0x7f, "com.example.app");
+/// let app_name = package.add_string("app_name", &ResTableConfig::default(), "My App")?;
+/// package.add_string("app_name", &"fr".parse().unwrap(), "Mon App")?;
+/// package.add_color("primary", &ResTableConfig::default(), 0xFF2196F3)?;
+/// let arsc = builder.build();
+/// assert_eq!(arsc.get(app_name).unwrap().key_name, "app_name");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ArscBuilder {
+    packages: Vec<PackageBuilder>,
+}
+
+impl ArscBuilder {
+    pub fn new() -> Self {
+        ArscBuilder::default()
+    }
+
+    /// return the builder of the package with the id, which is added if it does not exist yet
+    ///
+    /// # Arguments:
+    /// * id - the package id, e.g. `0x7f` for applications
+    /// * name - the package name. It is only used when the package is added
+    pub fn package(&mut self, id: u8, name: &str) -> &mut PackageBuilder {
+        let index = match self.packages.iter().position(|package| package.id == id) {
+            Some(index) => index,
+            None => {
+                self.packages.push(PackageBuilder::new(id, name));
+                self.packages.len() - 1
+            }
+        };
+        &mut self.packages[index]
+    }
+
+    /// Build the table. Strings are written in UTF-8, like aapt2 does
+    pub fn build(self) -> Arsc {
+        let mut global_string_pool = StringPool {
+            flags: StringPool::UTF8_FLAG,
+            strings: Vec::new(),
+            styles: Vec::new(),
+        };
+        let mut string_indices = HashMap::new();
+        let packages = self
+            .packages
+            .into_iter()
+            .map(|package| package.build(&mut global_string_pool, &mut string_indices))
+            .collect();
+        Arsc {
+            packages,
+            global_string_pool,
+            unknown_chunks: Vec::new(),
+            chunk_order: Vec::new(),
+        }
+    }
+}
+
+/// A value whose strings are not in the global string pool yet
+#[derive(Debug)]
+enum PendingValue {
+    String(String),
+    Plain(Value),
+    Bag {
+        parent: ResourceId,
+        values: Vec<(ResourceId, Value)>,
+    },
+}
+
+#[derive(Debug)]
+struct TypeBuilder {
+    name: String,
+    /// key names of the entries, where the entry id is the index
+    entries: Vec<String>,
+    /// entry ids indexed by key names
+    entry_indices: HashMap<String, usize>,
+    configs: Vec<(ResTableConfig, BTreeMap<usize, PendingValue>)>,
+}
+
+/// PackageBuilder collects the resources of a package. Type ids and entry ids
+/// are assigned in the order that the types and the names are first added,
+/// so a package holds at most 255 types of 65536 entries each
+#[derive(Debug)]
+pub struct PackageBuilder {
+    id: u8,
+    name: String,
+    types: Vec<TypeBuilder>,
+}
+
+impl PackageBuilder {
+    fn new(id: u8, name: &str) -> Self {
+        PackageBuilder {
+            id,
+            name: name.to_string(),
+            types: Vec::new(),
+        }
+    }
+
+    /// Add a string resource, e.g. `@string/app_name`
+    ///
+    /// # Arguments:
+    /// * name - the name of the resource
+    /// * config - the config that the value is used for
+    /// * value - the string
+    /// # Returns:
+    /// the id of the resource, or `Error::Overflow` if the ids run out
+    pub fn add_string(
+        &mut self,
+        name: &str,
+        config: &ResTableConfig,
+        value: &str,
+    ) -> Result<ResourceId> {
+        self.add(
+            "string",
+            name,
+            config,
+            PendingValue::String(value.to_string()),
+        )
+    }
+
+    /// Add a color resource, e.g. `@color/primary`
+    ///
+    /// # Arguments:
+    /// * name - the name of the resource
+    /// * config - the config that the value is used for
+    /// * argb - the color in the form of `0xAARRGGBB`
+    /// # Returns:
+    /// the id of the resource, or `Error::Overflow` if the ids run out
+    pub fn add_color(
+        &mut self,
+        name: &str,
+        config: &ResTableConfig,
+        argb: u32,
+    ) -> Result<ResourceId> {
+        self.add_value("color", name, config, ValueData::ColorArgb8(argb).into())
+    }
+
+    /// Add a dimension resource, e.g. `@dimen/margin`
+    ///
+    /// # Arguments:
+    /// * name - the name of the resource
+    /// * config - the config that the value is used for
+    /// * dimension - the dimension, e.g. `16dp`
+    /// # Returns:
+    /// the id of the resource, or `Error::Overflow` if the ids run out
+    pub fn add_dimension(
+        &mut self,
+        name: &str,
+        config: &ResTableConfig,
+        dimension: Dimension,
+    ) -> Result<ResourceId> {
+        self.add_value("dimen", name, config, dimension.into())
+    }
+
+    /// Add a resource with a plain value
+    ///
+    /// # Arguments:
+    /// * r#type - the type name of the resource, e.g. `integer`
+    /// * name - the name of the resource
+    /// * config - the config that the value is used for
+    /// * value - the value. Strings must be added with `add_string`
+    /// # Returns:
+    /// the id of the resource, `Error::Overflow` if the ids run out,
+    /// or `Error::BadHeader` if the value is a string
+    pub fn add_value(
+        &mut self,
+        r#type: &str,
+        name: &str,
+        config: &ResTableConfig,
+        value: Value,
+    ) -> Result<ResourceId> {
+        self.add(r#type, name, config, PendingValue::Plain(value))
+    }
+
+    /// Add a resource with a bag, e.g. a style or an array
+    ///
+    /// # Arguments:
+    /// * r#type - the type name of the resource, e.g. `style`
+    /// * name - the name of the resource
+    /// * config - the config that the value is used for
+    /// * bag - the bag, e.g. created by `ResourceValue::array`.
+    ///   A plain value is added as it is
+    /// # Returns:
+    /// the id of the resource, `Error::Overflow` if the ids run out,
+    /// or `Error::BadHeader` if the bag contains a string
+    pub fn add_bag(
+        &mut self,
+        r#type: &str,
+        name: &str,
+        config: &ResTableConfig,
+        bag: ResourceValue,
+    ) -> Result<ResourceId> {
+        let value = match bag {
+            ResourceValue::Bag { parent, values } => PendingValue::Bag { parent, values },
+            ResourceValue::Plain(value) => PendingValue::Plain(value),
+        };
+        self.add(r#type, name, config, value)
+    }
+
+    /// return the id of a resource that has been added
+    pub fn id_of(&self, r#type: &str, name: &str) -> Option<ResourceId> {
+        let type_index = self.types.iter().position(|it| it.name == r#type)?;
+        let entry_index = *self.types[type_index].entry_indices.get(name)?;
+        Some(self.resource_id(type_index, entry_index))
+    }
+
+    fn add(
+        &mut self,
+        r#type: &str,
+        name: &str,
+        config: &ResTableConfig,
+        value: PendingValue,
+    ) -> Result<ResourceId> {
+        // the index of a string value would point into a pool the builder has not created yet
+        let has_string = match &value {
+            PendingValue::String(_) => false,
+            PendingValue::Plain(value) => value.r#type == Value::TYPE_STRING,
+            PendingValue::Bag { values, .. } => values
+                .iter()
+                .any(|(_, value)| value.r#type == Value::TYPE_STRING),
+        };
+        if has_string {
+            return Err(Error::bad_header(
+                "string values must be added with add_string",
+                0,
+            ));
+        }
+        let type_index = match self.types.iter().position(|it| it.name == r#type) {
+            Some(index) => index,
+            None => {
+                // type ids start from 1 and must fit in a byte
+                if self.types.len() >= u8::MAX as usize {
+                    return Err(Error::Overflow {
+                        target: "u8",
+                        location: Default::default(),
+                    });
+                }
+                self.types.push(TypeBuilder {
+                    name: r#type.to_string(),
+                    entries: Vec::new(),
+                    entry_indices: HashMap::new(),
+                    configs: Vec::new(),
+                });
+                self.types.len() - 1
+            }
+        };
+        let type_builder = &mut self.types[type_index];
+        let entry_index = match type_builder.entry_indices.get(name) {
+            Some(index) => *index,
+            None => {
+                if type_builder.entries.len() > u16::MAX as usize {
+                    return Err(Error::Overflow {
+                        target: "u16",
+                        location: Default::default(),
+                    });
+                }
+                type_builder.entries.push(name.to_string());
+                type_builder
+                    .entry_indices
+                    .insert(name.to_string(), type_builder.entries.len() - 1);
+                type_builder.entries.len() - 1
+            }
+        };
+        let config_index = match type_builder.configs.iter().position(|(it, _)| it == config) {
+            Some(index) => index,
+            None => {
+                type_builder.configs.push((config.clone(), BTreeMap::new()));
+                type_builder.configs.len() - 1
+            }
+        };
+        type_builder.configs[config_index]
+            .1
+            .insert(entry_index, value);
+        Ok(self.resource_id(type_index, entry_index))
+    }
+
+    fn resource_id(&self, type_index: usize, entry_index: usize) -> ResourceId {
+        ResourceId::try_from_parts(self.id as u32, type_index + 1, entry_index)
+            .expect("the number of types and entries is checked when they are added")
+    }
+
+    fn build(
+        self,
+        global_string_pool: &mut StringPool,
+        string_indices: &mut HashMap<String, usize>,
+    ) -> Package {
+        // type names are written in UTF-16 and key names in UTF-8, like aapt2 does
+        let mut type_names = StringPool {
+            flags: 0,
+            strings: Vec::new(),
+            styles: Vec::new(),
+        };
+        let mut key_names = StringPool {
+            flags: StringPool::UTF8_FLAG,
+            strings: Vec::new(),
+            styles: Vec::new(),
+        };
+        let mut key_indices = HashMap::new();
+        let mut types = Vec::new();
+        for (index, type_builder) in self.types.into_iter().enumerate() {
+            let type_id = index + 1;
+            type_names.strings.push(type_builder.name);
+            let name_indices = type_builder
+                .entries
+                .into_iter()
+                .map(|key| key_names.intern(&mut key_indices, key))
+                .collect::<Vec<_>>();
+            // a spec flag tells which config fields the values of the entry vary on,
+            // so it collects the differences between every two configs of the entry
+            let mut spec_flags = vec![0; name_indices.len()];
+            for (index, (config, values)) in type_builder.configs.iter().enumerate() {
+                for (other_config, other_values) in &type_builder.configs[index + 1..] {
+                    let diff = config.diff(other_config);
+                    for entry_index in values.keys() {
+                        if other_values.contains_key(entry_index) {
+                            spec_flags[*entry_index] |= diff;
+                        }
+                    }
+                }
+            }
+            let configs = type_builder
+                .configs
+                .into_iter()
+                .map(|(config, values)| {
+                    let resources = values
+                        .into_iter()
+                        .map(|(spec_id, value)| {
+                            let (flags, value) = match value {
+                                PendingValue::String(string) => {
+                                    let index = global_string_pool.intern(string_indices, string);
+                                    (0, ResourceValue::Plain(ValueData::String(index).into()))
+                                }
+                                PendingValue::Plain(value) => (0, ResourceValue::Plain(value)),
+                                PendingValue::Bag { parent, values } => (
+                                    ResourceEntry::ENTRY_FLAG_COMPLEX,
+                                    ResourceValue::Bag { parent, values },
+                                ),
+                            };
+                            ResourceEntry {
+                                flags,
+                                name_index: name_indices[spec_id],
+                                value,
+                                spec_id,
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    Config {
+                        type_id,
+                        res0: 0,
+                        res1: 0,
+                        id: config,
+                        resources: Resources {
+                            missing_entries: name_indices.len() - resources.len(),
+                            resources,
+                            shared_entries: BTreeMap::new(),
+                        },
+                        // the header is written with the size that the config needs
+                        header_size: 0,
+                    }
+                })
+                .collect();
+            types.push(Type {
+                id: type_id,
+                specs: Some(Specs {
+                    type_id,
+                    res0: 0,
+                    res1: 0,
+                    specs: spec_flags
+                        .into_iter()
+                        .enumerate()
+                        .map(|(id, flags)| Spec::new(flags, id))
+                        .collect(),
+                    // header + type_id + res0 + res1 + entry_count
+                    header_size: 8 + 1 + 1 + 2 + 4,
+                }),
+                configs,
+            });
+        }
+        Package {
+            id: self.id as u32,
+            name: self.name,
+            type_names,
+            last_public_type: 0,
+            types,
+            key_names,
+            last_public_key: 0,
+            library: None,
+            overlayables: Vec::new(),
+            staged_aliases: Vec::new(),
+            unknown_chunks: Vec::new(),
+            chunk_order: Vec::new(),
+            name_index: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Android packs dimension and fraction values into a single `u32`, named complex.
+//! From the lowest bit, a complex holds 4 bits of unit, 2 bits of radix and 24 bits of signed mantissa.
+//! The radix tells how many of the mantissa bits are after the binary point.
+
+use crate::{Value, ValueData};
+use std::fmt::{Display, Formatter};
+
+const UNIT_MASK: u32 = 0xF;
+const RADIX_SHIFT: u32 = 4;
+const RADIX_MASK: u32 = 0x3;
+const MANTISSA_SHIFT: u32 = 8;
+const MANTISSA_MASK: u32 = 0xFF_FFFF;
+
+const RADIX_23P0: u32 = 0;
+const RADIX_16P7: u32 = 1;
+const RADIX_8P15: u32 = 2;
+const RADIX_0P23: u32 = 3;
+
+/// The multiplier to turn the mantissa into a float, indexed by the radix.
+/// The mantissa is read as the top 24 bits of an `i32`, hence the extra 8 bits in every divisor
+const RADIX_MULTIPLIERS: [f32; 4] = [
+    1.0 / (1u64 << 8) as f32,
+    1.0 / (1u64 << 15) as f32,
+    1.0 / (1u64 << 23) as f32,
+    1.0 / (1u64 << 31) as f32,
+];
+
+/// Decode the float value of a complex
+pub fn complex_to_f32(complex: u32) -> f32 {
+    let mantissa = (complex & (MANTISSA_MASK << MANTISSA_SHIFT)) as i32;
+    mantissa as f32 * RADIX_MULTIPLIERS[((complex >> RADIX_SHIFT) & RADIX_MASK) as usize]
+}
+
+/// Encode a float value into a complex with the unit bits left as zero.
+/// The most precise radix that can hold the integer part is picked, the same as aapt does
+pub fn f32_to_complex(value: f32) -> u32 {
+    let negative = value < 0.0;
+    let value = value.abs();
+    let bits = (value as f64 * (1u64 << 23) as f64 + 0.5) as u64;
+    let (radix, shift) = if bits & 0x7F_FFFF == 0 {
+        // no fractional part
+        (RADIX_23P0, 23)
+    } else if bits & 0xFFFF_FFFF_FF80_0000 == 0 {
+        // magnitude is less than 1
+        (RADIX_0P23, 0)
+    } else if bits & 0xFFFF_FFFF_8000_0000 == 0 {
+        // magnitude is less than 1 << 8
+        (RADIX_8P15, 8)
+    } else if bits & 0xFFFF_FF80_0000_0000 == 0 {
+        // magnitude is less than 1 << 16
+        (RADIX_16P7, 16)
+    } else {
+        (RADIX_23P0, 23)
+    };
+    let mut mantissa = ((bits >> shift) as u32) & MANTISSA_MASK;
+    if negative {
+        mantissa = mantissa.wrapping_neg() & MANTISSA_MASK;
+    }
+    (radix << RADIX_SHIFT) | (mantissa << MANTISSA_SHIFT)
+}
+
+/// The unit of a dimension value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DimensionUnit {
+    Px = 0,
+    Dp = 1,
+    Sp = 2,
+    Pt = 3,
+    In = 4,
+    Mm = 5,
+}
+
+impl DimensionUnit {
+    fn from_complex(complex: u32) -> Option<Self> {
+        let unit = match complex & UNIT_MASK {
+            0 => DimensionUnit::Px,
+            1 => DimensionUnit::Dp,
+            2 => DimensionUnit::Sp,
+            3 => DimensionUnit::Pt,
+            4 => DimensionUnit::In,
+            5 => DimensionUnit::Mm,
+            _ => return None,
+        };
+        Some(unit)
+    }
+
+    /// return the suffix of the unit used in resource xml, e.g. `dp`
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            DimensionUnit::Px => "px",
+            DimensionUnit::Dp => "dp",
+            DimensionUnit::Sp => "sp",
+            DimensionUnit::Pt => "pt",
+            DimensionUnit::In => "in",
+            DimensionUnit::Mm => "mm",
+        }
+    }
+}
+
+/// The unit of a fraction value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FractionUnit {
+    /// A fraction of the base size, written as `50%`
+    Base = 0,
+    /// A fraction of the parent size, written as `50%p`
+    Parent = 1,
+}
+
+impl FractionUnit {
+    fn from_complex(complex: u32) -> Option<Self> {
+        match complex & UNIT_MASK {
+            0 => Some(FractionUnit::Base),
+            1 => Some(FractionUnit::Parent),
+            _ => None,
+        }
+    }
+
+    /// return the suffix of the unit used in resource xml, e.g. `%p`
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            FractionUnit::Base => "%",
+            FractionUnit::Parent => "%p",
+        }
+    }
+}
+
+/// Dimension is the decoded complex data of a dimension value, e.g. `16.0dp`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dimension {
+    pub value: f32,
+    pub unit: DimensionUnit,
+}
+
+impl Dimension {
+    pub fn new(value: f32, unit: DimensionUnit) -> Self {
+        Dimension { value, unit }
+    }
+
+    /// Decode a dimension from its complex data
+    ///
+    /// # Argument:
+    /// * complex - the data of a dimension value
+    /// # Returns:
+    /// the dimension, or None if the unit is unknown
+    pub fn from_complex(complex: u32) -> Option<Self> {
+        let unit = DimensionUnit::from_complex(complex)?;
+        Some(Dimension::new(complex_to_f32(complex), unit))
+    }
+
+    /// Encode the dimension into complex data
+    pub fn to_complex(&self) -> u32 {
+        f32_to_complex(self.value) | self.unit as u32
+    }
+}
+
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}{}", self.value, self.unit.suffix())
+    }
+}
+
+impl From<Dimension> for ValueData {
+    fn from(dimension: Dimension) -> Self {
+        ValueData::Dimension(dimension.to_complex())
+    }
+}
+
+impl From<Dimension> for Value {
+    fn from(dimension: Dimension) -> Self {
+        ValueData::from(dimension).into()
+    }
+}
+
+/// Fraction is the decoded complex data of a fraction value.
+/// `value` is the fraction itself, so `50%` has a value of `0.5`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fraction {
+    pub value: f32,
+    pub unit: FractionUnit,
+}
+
+impl Fraction {
+    pub fn new(value: f32, unit: FractionUnit) -> Self {
+        Fraction { value, unit }
+    }
+
+    /// Decode a fraction from its complex data
+    ///
+    /// # Argument:
+    /// * complex - the data of a fraction value
+    /// # Returns:
+    /// the fraction, or None if the unit is unknown
+    pub fn from_complex(complex: u32) -> Option<Self> {
+        let unit = FractionUnit::from_complex(complex)?;
+        Some(Fraction::new(complex_to_f32(complex), unit))
+    }
+
+    /// Encode the fraction into complex data
+    pub fn to_complex(&self) -> u32 {
+        f32_to_complex(self.value) | self.unit as u32
+    }
+}
+
+impl Display for Fraction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.value * 100.0, self.unit.suffix())
+    }
+}
+
+impl From<Fraction> for ValueData {
+    fn from(fraction: Fraction) -> Self {
+        ValueData::Fraction(fraction.to_complex())
+    }
+}
+
+impl From<Fraction> for Value {
+    fn from(fraction: Fraction) -> Self {
+        ValueData::from(fraction).into()
+    }
+}
+
+impl ValueData {
+    /// return the decoded dimension if the data is a dimension with a known unit
+    pub fn dimension(&self) -> Option<Dimension> {
+        match *self {
+            ValueData::Dimension(complex) => Dimension::from_complex(complex),
+            _ => None,
+        }
+    }
+
+    /// return the decoded fraction if the data is a fraction with a known unit
+    pub fn fraction(&self) -> Option<Fraction> {
+        match *self {
+            ValueData::Fraction(complex) => Fraction::from_complex(complex),
+            _ => None,
+        }
+    }
+}
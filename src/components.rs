@@ -1,3 +1,7 @@
+use crate::lookup::NameIndex;
+use crate::ResTableConfig;
+use std::collections::{BTreeMap, HashMap};
+
 /// Header is the ResTable_header.
 /// Each chunk in an arsc file has a header
 #[derive(Debug)]
@@ -7,7 +11,7 @@ pub struct Header {
     pub size: u64,
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum ResourceType {
     Null = 0x0000,
     StringPool = 0x0001,
@@ -16,12 +20,17 @@ pub enum ResourceType {
     TableType = 0x0201,
     TableTypeSpec = 0x0202,
     TableLibrary = 0x0203,
+    TableOverlayable = 0x0204,
+    TableOverlayablePolicy = 0x0205,
+    TableStagedAlias = 0x0206,
 }
 
-impl From<u16> for ResourceType {
-    fn from(bits: u16) -> Self {
+impl TryFrom<u16> for ResourceType {
+    type Error = crate::Error;
+
+    fn try_from(bits: u16) -> Result<Self, Self::Error> {
         use ResourceType::*;
-        match bits {
+        Ok(match bits {
             0 => Null,
             1 => StringPool,
             2 => Table,
@@ -29,8 +38,16 @@ impl From<u16> for ResourceType {
             0x0201 => TableType,
             0x0202 => TableTypeSpec,
             0x0203 => TableLibrary,
-            bits => unreachable!("Unexpected bits: {bits}"),
-        }
+            0x0204 => TableOverlayable,
+            0x0205 => TableOverlayablePolicy,
+            0x0206 => TableStagedAlias,
+            resource_type => {
+                return Err(crate::Error::UnknownChunkType {
+                    resource_type,
+                    location: Default::default(),
+                })
+            }
+        })
     }
 }
 
@@ -44,6 +61,30 @@ impl From<u16> for ResourceType {
 pub struct Arsc {
     pub packages: Vec<Package>,
     pub global_string_pool: StringPool,
+    pub unknown_chunks: Vec<UnknownChunk>,
+    /// the order of the chunks in the table, as they were parsed.
+    /// Chunks that are not listed are written after the listed ones
+    pub chunk_order: Vec<TableChunk>,
+}
+
+/// A chunk in the table, referring to the field of `Arsc` that holds it
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum TableChunk {
+    GlobalStringPool,
+    /// the package at `packages[index]`
+    Package(usize),
+    /// the chunk at `unknown_chunks[index]`
+    Unknown(usize),
+}
+
+impl Arsc {
+    /// Find the finalized resource id that a staged resource id has been aliased to
+    /// in any of the packages
+    pub fn finalized_id(&self, staged_id: ResourceId) -> Option<ResourceId> {
+        self.packages
+            .iter()
+            .find_map(|package| package.finalized_id(staged_id))
+    }
 }
 
 /// A chunk with header type `ResTable_package`.
@@ -57,11 +98,200 @@ pub struct Arsc {
 pub struct Package {
     pub id: u32,
     pub name: String,
-    pub type_names: StringPool,
+    /// see `Package::type_names`
+    pub(crate) type_names: StringPool,
     pub last_public_type: u32,
-    pub types: Vec<Type>,
-    pub key_names: StringPool,
+    /// see `Package::types`
+    pub(crate) types: Vec<Type>,
+    /// see `Package::key_names`
+    pub(crate) key_names: StringPool,
     pub last_public_key: u32,
+    pub library: Option<Library>,
+    pub overlayables: Vec<Overlayable>,
+    /// the aliases of every staged alias chunk, one list per chunk
+    pub staged_aliases: Vec<Vec<StagedAlias>>,
+    pub unknown_chunks: Vec<UnknownChunk>,
+    /// the order of the chunks after the type names and key names, as they were parsed.
+    /// Chunks that are not listed are written after the listed ones
+    pub chunk_order: Vec<PackageChunk>,
+    /// resource ids indexed by names, built on the first lookup by name
+    pub(crate) name_index: NameIndex,
+}
+
+/// A chunk in the package, referring to the field of `Package` that holds it
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum PackageChunk {
+    /// the specs of the type at `types[type_index]`
+    Specs(usize),
+    /// the config at `types[type_index].configs[config_index]`
+    Config {
+        type_index: usize,
+        config_index: usize,
+    },
+    Library,
+    /// the overlayable at `overlayables[index]`
+    Overlayable(usize),
+    /// the staged alias chunk at `staged_aliases[index]`
+    StagedAliases(usize),
+    /// the chunk at `unknown_chunks[index]`
+    Unknown(usize),
+}
+
+impl Package {
+    /// return the type names string pool, where the name of `Type::id` is at `id - 1`
+    pub fn type_names(&self) -> &StringPool {
+        &self.type_names
+    }
+
+    /// return the type names string pool to modify.
+    /// The index of names is dropped, so it is rebuilt on the next lookup by name
+    pub fn type_names_mut(&mut self) -> &mut StringPool {
+        self.name_index.clear();
+        &mut self.type_names
+    }
+
+    /// return the types of this package, where the type with `Type::id` is at `id - 1`
+    pub fn types(&self) -> &Vec<Type> {
+        &self.types
+    }
+
+    /// return the types of this package to modify.
+    /// The index of names is dropped, so it is rebuilt on the next lookup by name
+    pub fn types_mut(&mut self) -> &mut Vec<Type> {
+        self.name_index.clear();
+        &mut self.types
+    }
+
+    /// return the key names string pool, indexed by `ResourceEntry::name_index`
+    pub fn key_names(&self) -> &StringPool {
+        &self.key_names
+    }
+
+    /// return the key names string pool to modify.
+    /// The index of names is dropped, so it is rebuilt on the next lookup by name
+    pub fn key_names_mut(&mut self) -> &mut StringPool {
+        self.name_index.clear();
+        &mut self.key_names
+    }
+
+    /// Find the finalized resource id that a staged resource id has been aliased to
+    ///
+    /// # Argument:
+    /// * staged_id - the staged resource id
+    /// # Returns:
+    /// the finalized resource id if the staged id has been finalized
+    pub fn finalized_id(&self, staged_id: ResourceId) -> Option<ResourceId> {
+        self.staged_aliases
+            .iter()
+            .flatten()
+            .find(|alias| alias.staged_id == staged_id)
+            .map(|alias| alias.finalized_id)
+    }
+
+    /// Compute the id of a resource entry in this package
+    ///
+    /// # Arguments:
+    /// * r#type - the type that owns the entry
+    /// * entry - the resource entry
+    /// # Returns:
+    /// the id, or None if the ids of the package, the type or the entry do not fit in it
+    pub fn resource_id(&self, r#type: &Type, entry: &ResourceEntry) -> Option<ResourceId> {
+        ResourceId::try_from_parts(self.id, r#type.id, entry.spec_id)
+    }
+}
+
+/// Library is a chunk with header type `RES_TABLE_LIBRARY_TYPE`.
+/// It maps the package ids assigned at build time to the names
+/// of the shared libraries this package references
+#[derive(Debug)]
+pub struct Library {
+    pub entries: Vec<LibraryEntry>,
+}
+
+#[derive(Debug)]
+pub struct LibraryEntry {
+    /// The package id of the shared library assigned at build time
+    pub package_id: u32,
+    pub package_name: String,
+}
+
+/// Overlayable is a chunk with header type `RES_TABLE_OVERLAYABLE_TYPE`.
+/// It declares a group of resources that runtime resource overlays are allowed to overlay
+#[derive(Debug)]
+pub struct Overlayable {
+    pub name: String,
+    /// The component responsible for enabling and disabling overlays targeting this chunk
+    pub actor: String,
+    pub policies: Vec<OverlayablePolicy>,
+}
+
+/// OverlayablePolicy is a chunk with header type `RES_TABLE_OVERLAYABLE_POLICY_TYPE`.
+/// It lists the resources that overlays fulfilling the `flags` are allowed to overlay
+#[derive(Debug)]
+pub struct OverlayablePolicy {
+    pub flags: PolicyFlags,
+    /// ids of the resources that this policy applies to
+    pub entries: Vec<ResourceId>,
+}
+
+/// Flags of an overlayable policy describing which overlays
+/// are allowed to overlay the resources
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct PolicyFlags(pub u32);
+
+impl PolicyFlags {
+    pub const NONE: PolicyFlags = PolicyFlags(0x0000_0000);
+    /// Any overlay can overlay these resources
+    pub const PUBLIC: PolicyFlags = PolicyFlags(0x0000_0001);
+    /// The overlay must reside on the system partition
+    pub const SYSTEM_PARTITION: PolicyFlags = PolicyFlags(0x0000_0002);
+    /// The overlay must reside on the vendor partition
+    pub const VENDOR_PARTITION: PolicyFlags = PolicyFlags(0x0000_0004);
+    /// The overlay must reside on the product partition
+    pub const PRODUCT_PARTITION: PolicyFlags = PolicyFlags(0x0000_0008);
+    /// The overlay must be signed with the same signature as the target package
+    pub const SIGNATURE: PolicyFlags = PolicyFlags(0x0000_0010);
+    /// The overlay must reside on the odm partition
+    pub const ODM_PARTITION: PolicyFlags = PolicyFlags(0x0000_0020);
+    /// The overlay must reside on the oem partition
+    pub const OEM_PARTITION: PolicyFlags = PolicyFlags(0x0000_0040);
+    /// The overlay must be signed with the same signature as the actor
+    pub const ACTOR_SIGNATURE: PolicyFlags = PolicyFlags(0x0000_0080);
+    /// The overlay must be signed with the same signature as the reference package
+    pub const CONFIG_SIGNATURE: PolicyFlags = PolicyFlags(0x0000_0100);
+
+    /// return true if all the flags in `other` are also set in `self`
+    pub fn contains(self, other: PolicyFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for PolicyFlags {
+    type Output = PolicyFlags;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        PolicyFlags(self.0 | rhs.0)
+    }
+}
+
+/// StagedAlias is an entry in the chunk with header type `RES_TABLE_STAGED_ALIAS_TYPE`.
+/// It maps a resource id that was staged during development to its finalized resource id
+#[derive(Debug)]
+pub struct StagedAlias {
+    pub staged_id: ResourceId,
+    pub finalized_id: ResourceId,
+}
+
+/// UnknownChunk is a chunk whose type is not supported at its position.
+/// It is kept as raw bytes, so it can be written back unchanged
+/// at its position in the `chunk_order` of its parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownChunk {
+    pub resource_type: u16,
+    /// the bytes of the chunk header after `resource_type`, `header_size` and `size`
+    pub header: Vec<u8>,
+    /// the bytes of the chunk after its header
+    pub body: Vec<u8>,
 }
 
 /// StringPool is a chunk that stores all the strings used in this chunk.
@@ -86,6 +316,18 @@ impl StringPool {
     pub fn use_utf8(&self) -> bool {
         self.flags & Self::UTF8_FLAG != 0
     }
+
+    /// return the index of the string, which is appended if it is not in the pool yet
+    ///
+    /// # Arguments:
+    /// * indices - the indices of the strings that are already interned
+    /// * string - the string to find or append
+    pub(crate) fn intern(&mut self, indices: &mut HashMap<String, usize>, string: String) -> usize {
+        *indices.entry(string).or_insert_with_key(|string| {
+            self.strings.push(string.clone());
+            self.strings.len() - 1
+        })
+    }
 }
 
 /// Style information associated with a string in the string pool
@@ -156,26 +398,117 @@ impl Spec {
 #[derive(Debug)]
 pub struct Config {
     pub type_id: usize,
+    /// flags describing how the entries are encoded, e.g. `FLAG_SPARSE`.
+    /// It is named after the field that was reserved before the flags were introduced
     pub res0: u8,
     pub res1: u16,
-    pub id: Vec<u8>,
+    pub id: ResTableConfig,
     pub resources: Resources,
+    /// the size of the chunk header. Headers written by newer tools can be longer
+    /// than the known fields, and the extra bytes are written back as zeros.
+    /// A size smaller than the known fields need is ignored when written
     pub header_size: u16,
 }
 
+impl Config {
+    /// A flag indicating the entry offsets are encoded as `(u16 index, u16 offset / 4)` pairs,
+    /// and only the entries that exist are listed
+    pub const FLAG_SPARSE: u8 = 0x01;
+    /// A flag indicating the entry offsets are encoded as `u16` values of `offset / 4`,
+    /// with `0xFFFF` meaning no entry
+    pub const FLAG_OFFSET16: u8 = 0x02;
+
+    /// return true if the entries of this config are sparsely encoded
+    pub fn is_sparse(&self) -> bool {
+        self.res0 & Self::FLAG_SPARSE != 0
+    }
+
+    /// Turn the sparse encoding on or off for the entries of this config
+    pub fn set_sparse(&mut self, sparse: bool) {
+        if sparse {
+            self.res0 |= Self::FLAG_SPARSE;
+        } else {
+            self.res0 &= !Self::FLAG_SPARSE;
+        }
+    }
+
+    /// return true if the entry offsets of this config are encoded as 16 bits values
+    pub fn is_offset16(&self) -> bool {
+        self.res0 & Self::FLAG_OFFSET16 != 0
+    }
+
+    /// Turn the 16 bits entry offset encoding on or off for the entries of this config
+    pub fn set_offset16(&mut self, offset16: bool) {
+        if offset16 {
+            self.res0 |= Self::FLAG_OFFSET16;
+        } else {
+            self.res0 &= !Self::FLAG_OFFSET16;
+        }
+    }
+
+    /// Use the sparse encoding if it results in a smaller chunk than the dense encoding
+    /// and all the entries can be addressed by it, otherwise use the dense encoding.
+    ///
+    /// Notice: sparse encoding is only understood by Android O (API 26) and above
+    ///
+    /// # Returns:
+    /// true if the sparse encoding is chosen
+    pub fn use_sparse_if_smaller(&mut self) -> bool {
+        // dense offsets take 2 or 4 bytes each depending on `FLAG_OFFSET16`
+        self.set_sparse(true);
+        let sparse_size = self.entry_offsets_size();
+        self.set_sparse(false);
+        let smaller = sparse_size < self.entry_offsets_size();
+        let addressable = self.resources.entry_count() <= u16::MAX as usize + 1
+            && self.resources.entries_size() / 4 <= u16::MAX as usize;
+        self.set_sparse(smaller && addressable);
+        self.is_sparse()
+    }
+
+    /// The number of entry offsets written in the chunk
+    pub(crate) fn entry_offset_count(&self) -> usize {
+        if self.is_sparse() {
+            self.resources.resources.len()
+        } else {
+            self.resources.entry_count()
+        }
+    }
+
+    /// The number of bytes each entry offset takes in the chunk
+    pub(crate) fn entry_offset_size(&self) -> usize {
+        if !self.is_sparse() && self.is_offset16() {
+            2
+        } else {
+            4
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Resources {
     pub resources: Vec<ResourceEntry>,
     pub missing_entries: usize,
+    /// Resources whose entry is shared with another resource, as pairs of
+    /// `spec_id` and the `spec_id` of the resource that owns the entry.
+    /// The shared entry is only written once if both resources are still identical
+    pub shared_entries: BTreeMap<usize, usize>,
 }
 
 impl Resources {
+    /// Find the resource associated with the spec at `specs[spec_id]`
+    pub fn get(&self, spec_id: usize) -> Option<&ResourceEntry> {
+        self.resources
+            .binary_search_by_key(&spec_id, |resource| resource.spec_id)
+            .ok()
+            .map(|index| &self.resources[index])
+    }
+
     pub(crate) fn entry_count(&self) -> usize {
         self.missing_entries + self.resources.len()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ResourceEntry {
     pub flags: u16,
     pub name_index: usize,
@@ -187,10 +520,127 @@ pub struct ResourceEntry {
 impl ResourceEntry {
     /// A flag indicating whether the resource is a plain value or a bag of values
     pub(crate) const ENTRY_FLAG_COMPLEX: u16 = 0x0001;
+    /// A flag indicating the entry is written in the compact form, where the key index
+    /// takes the place of the size, and the value type and data are packed into the entry
+    pub(crate) const ENTRY_FLAG_COMPACT: u16 = 0x0008;
 
     pub fn is_bag(&self) -> bool {
         self.flags & Self::ENTRY_FLAG_COMPLEX != 0
     }
+
+    /// return true if the entry is written in the compact form
+    pub fn is_compact(&self) -> bool {
+        self.flags & Self::ENTRY_FLAG_COMPACT != 0
+    }
+
+    /// Write the entry in the compact form or not.
+    /// Only plain values with a key index that fits in 16 bits can be compact.
+    ///
+    /// Notice: compact entries are only understood by Android U (API 34) and above
+    ///
+    /// # Returns:
+    /// true if the entry will be written in the compact form
+    pub fn set_compact(&mut self, compact: bool) -> bool {
+        self.flags &= !Self::ENTRY_FLAG_COMPACT;
+        if compact && self.can_be_compact() {
+            self.flags |= Self::ENTRY_FLAG_COMPACT;
+        }
+        self.is_compact()
+    }
+
+    /// return true if both entries have the same content, regardless of their `spec_id`
+    pub(crate) fn same_entry(&self, other: &ResourceEntry) -> bool {
+        self.flags == other.flags
+            && self.name_index == other.name_index
+            && self.value == other.value
+    }
+
+    /// return true if the entry is compact and it can be written in the compact form
+    pub(crate) fn use_compact_form(&self) -> bool {
+        self.is_compact() && self.can_be_compact()
+    }
+
+    fn can_be_compact(&self) -> bool {
+        match &self.value {
+            ResourceValue::Plain(value) => {
+                !self.is_bag()
+                    && self.name_index <= u16::MAX as usize
+                    && value.size == 8
+                    && value.zero == 0
+            }
+            ResourceValue::Bag { .. } => false,
+        }
+    }
+}
+
+/// ResourceId is the id of a resource, in the form of `0xPPTTEEEE`,
+/// where `PP` is the package id, `TT` is the type id and `EEEE` is the entry id
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct ResourceId(pub u32);
+
+impl ResourceId {
+    pub fn new(package: u8, type_id: u8, entry: u16) -> Self {
+        ResourceId((package as u32) << 24 | (type_id as u32) << 16 | entry as u32)
+    }
+
+    /// Compute the id from the ids in the parsed structure
+    ///
+    /// # Arguments:
+    /// * package_id - the `Package::id`
+    /// * type_id - the `Type::id`
+    /// * spec_id - the `ResourceEntry::spec_id`
+    /// # Returns:
+    /// the id, or None if a part is out of its range, e.g. a package id above `0xFF`
+    pub fn try_from_parts(package_id: u32, type_id: usize, spec_id: usize) -> Option<Self> {
+        Some(ResourceId::new(
+            u8::try_from(package_id).ok()?,
+            u8::try_from(type_id).ok()?,
+            u16::try_from(spec_id).ok()?,
+        ))
+    }
+
+    pub fn package(&self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    pub fn type_id(&self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    pub fn entry(&self) -> u16 {
+        self.0 as u16
+    }
+}
+
+impl From<u32> for ResourceId {
+    fn from(id: u32) -> Self {
+        ResourceId(id)
+    }
+}
+
+impl From<ResourceId> for u32 {
+    fn from(id: ResourceId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#010x}", self.0)
+    }
+}
+
+impl std::str::FromStr for ResourceId {
+    type Err = std::num::ParseIntError;
+
+    /// Parse a hex id, with or without the `0x` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        u32::from_str_radix(hex, 16).map(ResourceId)
+    }
 }
 
 /// Resource values can have two types:
@@ -199,31 +649,160 @@ impl ResourceEntry {
 /// 2. Bag
 ///
 /// Bag is a collection of values with a `parent` pointer
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum ResourceValue {
     Bag {
-        parent: u32,
-        values: Vec<(u32, Value)>,
+        parent: ResourceId,
+        /// values of the bag, keyed by the id of the attribute they are assigned to
+        values: Vec<(ResourceId, Value)>,
     },
     Plain(Value),
 }
 
-///
-#[derive(Debug, Eq, PartialEq)]
+/// Value is the `Res_value` that holds a single typed data
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Value {
     pub size: u16,
     pub zero: u8,
     pub r#type: u8,
-    /// data_index points to `global_string_pool[data_index]` to represent a string
+    /// the raw data, whose meaning depends on `r#type`, e.g. it points to
+    /// `global_string_pool[data_index]` to represent a string. See `Value::data` for a typed view
     pub data_index: usize,
 }
 
 impl Value {
-    const TYPE_STRING: u8 = 0x03;
+    pub const TYPE_NULL: u8 = 0x00;
+    pub const TYPE_REFERENCE: u8 = 0x01;
+    pub const TYPE_ATTRIBUTE: u8 = 0x02;
+    pub const TYPE_STRING: u8 = 0x03;
+    pub const TYPE_FLOAT: u8 = 0x04;
+    pub const TYPE_DIMENSION: u8 = 0x05;
+    pub const TYPE_FRACTION: u8 = 0x06;
+    pub const TYPE_DYNAMIC_REFERENCE: u8 = 0x07;
+    pub const TYPE_DYNAMIC_ATTRIBUTE: u8 = 0x08;
+    pub const TYPE_INT_DEC: u8 = 0x10;
+    pub const TYPE_INT_HEX: u8 = 0x11;
+    pub const TYPE_INT_BOOLEAN: u8 = 0x12;
+    pub const TYPE_INT_COLOR_ARGB8: u8 = 0x1C;
+    pub const TYPE_INT_COLOR_RGB8: u8 = 0x1D;
+    pub const TYPE_INT_COLOR_ARGB4: u8 = 0x1E;
+    pub const TYPE_INT_COLOR_RGB4: u8 = 0x1F;
+
+    /// `data_index` of a null value meaning the value is undefined
+    pub const DATA_NULL_UNDEFINED: u32 = 0;
+    /// `data_index` of a null value meaning the value is explicitly empty
+    pub const DATA_NULL_EMPTY: u32 = 1;
 
     /// return true if the type of the Value represents a string
-    #[allow(dead_code)]
     pub fn is_string(&self) -> bool {
-        self.r#type & Self::TYPE_STRING != 0
+        self.r#type == Self::TYPE_STRING
+    }
+
+    /// return the typed data of this value
+    pub fn data(&self) -> ValueData {
+        ValueData::from(self)
+    }
+}
+
+/// ValueData is the typed view of the data in a `Value`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ValueData {
+    /// An undefined value
+    Null,
+    /// A value that is explicitly defined as empty
+    Empty,
+    /// A reference to another resource, e.g. `@string/app_name`
+    Reference(ResourceId),
+    /// A reference to an attribute in the theme, e.g. `?attr/colorPrimary`
+    Attribute(ResourceId),
+    /// The index of a string in the global string pool
+    String(usize),
+    Float(f32),
+    /// A complex dimension value, e.g. `16dp`
+    Dimension(u32),
+    /// A complex fraction value, e.g. `50%`
+    Fraction(u32),
+    /// A reference whose package id needs to be resolved through the library
+    DynamicReference(ResourceId),
+    /// An attribute whose package id needs to be resolved through the library
+    DynamicAttribute(ResourceId),
+    IntDec(i32),
+    IntHex(u32),
+    IntBoolean(bool),
+    ColorArgb8(u32),
+    ColorRgb8(u32),
+    ColorArgb4(u32),
+    ColorRgb4(u32),
+    /// A value whose type is not known by this crate
+    Unknown {
+        r#type: u8,
+        data: u32,
+    },
+}
+
+impl ValueData {
+    /// return the `r#type` and the raw data of the `Value` representing this data
+    pub fn to_raw(&self) -> (u8, u32) {
+        match *self {
+            ValueData::Null => (Value::TYPE_NULL, Value::DATA_NULL_UNDEFINED),
+            ValueData::Empty => (Value::TYPE_NULL, Value::DATA_NULL_EMPTY),
+            ValueData::Reference(id) => (Value::TYPE_REFERENCE, id.0),
+            ValueData::Attribute(id) => (Value::TYPE_ATTRIBUTE, id.0),
+            ValueData::String(index) => (Value::TYPE_STRING, index as u32),
+            ValueData::Float(float) => (Value::TYPE_FLOAT, float.to_bits()),
+            ValueData::Dimension(complex) => (Value::TYPE_DIMENSION, complex),
+            ValueData::Fraction(complex) => (Value::TYPE_FRACTION, complex),
+            ValueData::DynamicReference(id) => (Value::TYPE_DYNAMIC_REFERENCE, id.0),
+            ValueData::DynamicAttribute(id) => (Value::TYPE_DYNAMIC_ATTRIBUTE, id.0),
+            ValueData::IntDec(int) => (Value::TYPE_INT_DEC, int as u32),
+            ValueData::IntHex(int) => (Value::TYPE_INT_HEX, int),
+            // Android encodes true as all bits set
+            ValueData::IntBoolean(boolean) => {
+                (Value::TYPE_INT_BOOLEAN, if boolean { u32::MAX } else { 0 })
+            }
+            ValueData::ColorArgb8(color) => (Value::TYPE_INT_COLOR_ARGB8, color),
+            ValueData::ColorRgb8(color) => (Value::TYPE_INT_COLOR_RGB8, color),
+            ValueData::ColorArgb4(color) => (Value::TYPE_INT_COLOR_ARGB4, color),
+            ValueData::ColorRgb4(color) => (Value::TYPE_INT_COLOR_RGB4, color),
+            ValueData::Unknown { r#type, data } => (r#type, data),
+        }
+    }
+}
+
+impl From<&Value> for ValueData {
+    fn from(value: &Value) -> Self {
+        let data = value.data_index as u32;
+        match value.r#type {
+            Value::TYPE_NULL if data == Value::DATA_NULL_EMPTY => ValueData::Empty,
+            Value::TYPE_NULL => ValueData::Null,
+            Value::TYPE_REFERENCE => ValueData::Reference(ResourceId(data)),
+            Value::TYPE_ATTRIBUTE => ValueData::Attribute(ResourceId(data)),
+            Value::TYPE_STRING => ValueData::String(data as usize),
+            Value::TYPE_FLOAT => ValueData::Float(f32::from_bits(data)),
+            Value::TYPE_DIMENSION => ValueData::Dimension(data),
+            Value::TYPE_FRACTION => ValueData::Fraction(data),
+            Value::TYPE_DYNAMIC_REFERENCE => ValueData::DynamicReference(ResourceId(data)),
+            Value::TYPE_DYNAMIC_ATTRIBUTE => ValueData::DynamicAttribute(ResourceId(data)),
+            Value::TYPE_INT_DEC => ValueData::IntDec(data as i32),
+            Value::TYPE_INT_HEX => ValueData::IntHex(data),
+            Value::TYPE_INT_BOOLEAN => ValueData::IntBoolean(data != 0),
+            Value::TYPE_INT_COLOR_ARGB8 => ValueData::ColorArgb8(data),
+            Value::TYPE_INT_COLOR_RGB8 => ValueData::ColorRgb8(data),
+            Value::TYPE_INT_COLOR_ARGB4 => ValueData::ColorArgb4(data),
+            Value::TYPE_INT_COLOR_RGB4 => ValueData::ColorRgb4(data),
+            r#type => ValueData::Unknown { r#type, data },
+        }
+    }
+}
+
+impl From<ValueData> for Value {
+    fn from(data: ValueData) -> Self {
+        let (r#type, data) = data.to_raw();
+        Value {
+            size: 8,
+            zero: 0,
+            r#type,
+            data_index: data as usize,
+        }
     }
 }
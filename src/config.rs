@@ -0,0 +1,950 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// ResTableConfig is the `ResTable_config` struct,
+/// describing the device configuration that a `Config` chunk targets.
+///
+/// The struct has grown over Android releases, from 28 bytes to 64 bytes.
+/// Only the fields within `size` bytes are encoded, so the configs written
+/// by older tools are written back unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResTableConfig {
+    /// the size of the encoded struct in bytes
+    pub size: u32,
+    pub mcc: u16,
+    pub mnc: u16,
+    /// the packed language code, see `language_code` and `set_language_code`
+    pub language: [u8; 2],
+    /// the packed region code, see `region_code` and `set_region_code`
+    pub country: [u8; 2],
+    pub orientation: u8,
+    pub touchscreen: u8,
+    pub density: u16,
+    pub keyboard: u8,
+    pub navigation: u8,
+    pub input_flags: u8,
+    pub grammatical_inflection: u8,
+    pub screen_width: u16,
+    pub screen_height: u16,
+    pub sdk_version: u16,
+    pub minor_version: u16,
+    pub screen_layout: u8,
+    pub ui_mode: u8,
+    pub smallest_screen_width_dp: u16,
+    pub screen_width_dp: u16,
+    pub screen_height_dp: u16,
+    pub locale_script: [u8; 4],
+    pub locale_variant: [u8; 8],
+    pub screen_layout2: u8,
+    pub color_mode: u8,
+    pub screen_config_pad2: u16,
+    /// non-zero if `locale_script` is computed instead of being specified
+    pub locale_script_was_computed: u8,
+    pub locale_numbering_system: [u8; 8],
+    /// the padding bytes at the end of the 64 bytes struct
+    pub end_padding: [u8; 3],
+    /// the bytes beyond the 64 bytes known by this crate
+    pub trailing: Vec<u8>,
+}
+
+impl Default for ResTableConfig {
+    fn default() -> Self {
+        ResTableConfig {
+            size: Self::SIZE as u32,
+            mcc: 0,
+            mnc: 0,
+            language: [0; 2],
+            country: [0; 2],
+            orientation: 0,
+            touchscreen: 0,
+            density: 0,
+            keyboard: 0,
+            navigation: 0,
+            input_flags: 0,
+            grammatical_inflection: 0,
+            screen_width: 0,
+            screen_height: 0,
+            sdk_version: 0,
+            minor_version: 0,
+            screen_layout: 0,
+            ui_mode: 0,
+            smallest_screen_width_dp: 0,
+            screen_width_dp: 0,
+            screen_height_dp: 0,
+            locale_script: [0; 4],
+            locale_variant: [0; 8],
+            screen_layout2: 0,
+            color_mode: 0,
+            screen_config_pad2: 0,
+            locale_script_was_computed: 0,
+            locale_numbering_system: [0; 8],
+            end_padding: [0; 3],
+            trailing: Vec::new(),
+        }
+    }
+}
+
+/// DeviceConfig describes the configuration of a device, e.g. its locale and density,
+/// which is used to pick the best matching resources
+pub type DeviceConfig = ResTableConfig;
+
+impl ResTableConfig {
+    /// The size of the struct with all the fields known by this crate
+    pub const SIZE: usize = 64;
+
+    /// `mnc` value of a mobile network code that is 00
+    pub const MNC_ZERO: u16 = 0xFFFF;
+
+    pub const ORIENTATION_PORT: u8 = 0x01;
+    pub const ORIENTATION_LAND: u8 = 0x02;
+    pub const ORIENTATION_SQUARE: u8 = 0x03;
+
+    pub const TOUCHSCREEN_NOTOUCH: u8 = 0x01;
+    pub const TOUCHSCREEN_STYLUS: u8 = 0x02;
+    pub const TOUCHSCREEN_FINGER: u8 = 0x03;
+
+    pub const DENSITY_DEFAULT: u16 = 0;
+    pub const DENSITY_LOW: u16 = 120;
+    pub const DENSITY_MEDIUM: u16 = 160;
+    pub const DENSITY_TV: u16 = 213;
+    pub const DENSITY_HIGH: u16 = 240;
+    pub const DENSITY_XHIGH: u16 = 320;
+    pub const DENSITY_XXHIGH: u16 = 480;
+    pub const DENSITY_XXXHIGH: u16 = 640;
+    pub const DENSITY_ANY: u16 = 0xFFFE;
+    pub const DENSITY_NONE: u16 = 0xFFFF;
+
+    pub const KEYBOARD_NOKEYS: u8 = 0x01;
+    pub const KEYBOARD_QWERTY: u8 = 0x02;
+    pub const KEYBOARD_12KEY: u8 = 0x03;
+
+    pub const NAVIGATION_NONAV: u8 = 0x01;
+    pub const NAVIGATION_DPAD: u8 = 0x02;
+    pub const NAVIGATION_TRACKBALL: u8 = 0x03;
+    pub const NAVIGATION_WHEEL: u8 = 0x04;
+
+    pub const MASK_KEYSHIDDEN: u8 = 0x03;
+    pub const KEYSHIDDEN_NO: u8 = 0x01;
+    pub const KEYSHIDDEN_YES: u8 = 0x02;
+    pub const KEYSHIDDEN_SOFT: u8 = 0x03;
+
+    pub const MASK_NAVHIDDEN: u8 = 0x0C;
+    pub const NAVHIDDEN_NO: u8 = 0x04;
+    pub const NAVHIDDEN_YES: u8 = 0x08;
+
+    pub const MASK_GRAMMATICAL_GENDER: u8 = 0x03;
+    pub const GRAMMATICAL_GENDER_NEUTER: u8 = 0x01;
+    pub const GRAMMATICAL_GENDER_FEMININE: u8 = 0x02;
+    pub const GRAMMATICAL_GENDER_MASCULINE: u8 = 0x03;
+
+    pub const MASK_SCREENSIZE: u8 = 0x0F;
+    pub const SCREENSIZE_SMALL: u8 = 0x01;
+    pub const SCREENSIZE_NORMAL: u8 = 0x02;
+    pub const SCREENSIZE_LARGE: u8 = 0x03;
+    pub const SCREENSIZE_XLARGE: u8 = 0x04;
+
+    pub const MASK_SCREENLONG: u8 = 0x30;
+    pub const SCREENLONG_NO: u8 = 0x10;
+    pub const SCREENLONG_YES: u8 = 0x20;
+
+    pub const MASK_LAYOUTDIR: u8 = 0xC0;
+    pub const LAYOUTDIR_LTR: u8 = 0x40;
+    pub const LAYOUTDIR_RTL: u8 = 0x80;
+
+    pub const MASK_UI_MODE_TYPE: u8 = 0x0F;
+    pub const UI_MODE_TYPE_NORMAL: u8 = 0x01;
+    pub const UI_MODE_TYPE_DESK: u8 = 0x02;
+    pub const UI_MODE_TYPE_CAR: u8 = 0x03;
+    pub const UI_MODE_TYPE_TELEVISION: u8 = 0x04;
+    pub const UI_MODE_TYPE_APPLIANCE: u8 = 0x05;
+    pub const UI_MODE_TYPE_WATCH: u8 = 0x06;
+    pub const UI_MODE_TYPE_VR_HEADSET: u8 = 0x07;
+
+    pub const MASK_UI_MODE_NIGHT: u8 = 0x30;
+    pub const UI_MODE_NIGHT_NO: u8 = 0x10;
+    pub const UI_MODE_NIGHT_YES: u8 = 0x20;
+
+    pub const MASK_SCREENROUND: u8 = 0x03;
+    pub const SCREENROUND_NO: u8 = 0x01;
+    pub const SCREENROUND_YES: u8 = 0x02;
+
+    pub const MASK_WIDE_COLOR_GAMUT: u8 = 0x03;
+    pub const WIDE_COLOR_GAMUT_NO: u8 = 0x01;
+    pub const WIDE_COLOR_GAMUT_YES: u8 = 0x02;
+
+    pub const MASK_HDR: u8 = 0x0C;
+    pub const HDR_NO: u8 = 0x04;
+    pub const HDR_YES: u8 = 0x08;
+
+    /// Bits returned by `diff`, which are also the flags of a `Spec`
+    pub const CONFIG_MCC: u32 = 0x0001;
+    pub const CONFIG_MNC: u32 = 0x0002;
+    pub const CONFIG_LOCALE: u32 = 0x0004;
+    pub const CONFIG_TOUCHSCREEN: u32 = 0x0008;
+    pub const CONFIG_KEYBOARD: u32 = 0x0010;
+    pub const CONFIG_KEYBOARD_HIDDEN: u32 = 0x0020;
+    pub const CONFIG_NAVIGATION: u32 = 0x0040;
+    pub const CONFIG_ORIENTATION: u32 = 0x0080;
+    pub const CONFIG_DENSITY: u32 = 0x0100;
+    pub const CONFIG_SCREEN_SIZE: u32 = 0x0200;
+    pub const CONFIG_VERSION: u32 = 0x0400;
+    pub const CONFIG_SCREEN_LAYOUT: u32 = 0x0800;
+    pub const CONFIG_UI_MODE: u32 = 0x1000;
+    pub const CONFIG_SMALLEST_SCREEN_SIZE: u32 = 0x2000;
+    pub const CONFIG_LAYOUTDIR: u32 = 0x4000;
+    pub const CONFIG_SCREEN_ROUND: u32 = 0x8000;
+    pub const CONFIG_COLOR_MODE: u32 = 0x10000;
+    pub const CONFIG_GRAMMATICAL_GENDER: u32 = 0x20000;
+
+    /// return the `CONFIG_*` bits of the fields that differ between two configs,
+    /// following `ResTable_config::diff` of Android
+    pub fn diff(&self, other: &ResTableConfig) -> u32 {
+        let differences = [
+            (self.mcc != other.mcc, Self::CONFIG_MCC),
+            (self.mnc != other.mnc, Self::CONFIG_MNC),
+            (
+                self.orientation != other.orientation,
+                Self::CONFIG_ORIENTATION,
+            ),
+            (self.density != other.density, Self::CONFIG_DENSITY),
+            (
+                self.touchscreen != other.touchscreen,
+                Self::CONFIG_TOUCHSCREEN,
+            ),
+            (
+                (self.input_flags ^ other.input_flags)
+                    & (Self::MASK_KEYSHIDDEN | Self::MASK_NAVHIDDEN)
+                    != 0,
+                Self::CONFIG_KEYBOARD_HIDDEN,
+            ),
+            (self.keyboard != other.keyboard, Self::CONFIG_KEYBOARD),
+            (self.navigation != other.navigation, Self::CONFIG_NAVIGATION),
+            (
+                (self.screen_width, self.screen_height)
+                    != (other.screen_width, other.screen_height)
+                    || (self.screen_width_dp, self.screen_height_dp)
+                        != (other.screen_width_dp, other.screen_height_dp),
+                Self::CONFIG_SCREEN_SIZE,
+            ),
+            (
+                (self.sdk_version, self.minor_version) != (other.sdk_version, other.minor_version),
+                Self::CONFIG_VERSION,
+            ),
+            (
+                (self.screen_layout ^ other.screen_layout) & Self::MASK_LAYOUTDIR != 0,
+                Self::CONFIG_LAYOUTDIR,
+            ),
+            (
+                (self.screen_layout ^ other.screen_layout) & !Self::MASK_LAYOUTDIR != 0,
+                Self::CONFIG_SCREEN_LAYOUT,
+            ),
+            (
+                (self.screen_layout2 ^ other.screen_layout2) & Self::MASK_SCREENROUND != 0,
+                Self::CONFIG_SCREEN_ROUND,
+            ),
+            (
+                (self.color_mode ^ other.color_mode)
+                    & (Self::MASK_WIDE_COLOR_GAMUT | Self::MASK_HDR)
+                    != 0,
+                Self::CONFIG_COLOR_MODE,
+            ),
+            (self.ui_mode != other.ui_mode, Self::CONFIG_UI_MODE),
+            (
+                self.smallest_screen_width_dp != other.smallest_screen_width_dp,
+                Self::CONFIG_SMALLEST_SCREEN_SIZE,
+            ),
+            (
+                self.grammatical_inflection != other.grammatical_inflection,
+                Self::CONFIG_GRAMMATICAL_GENDER,
+            ),
+            (
+                self.language != other.language
+                    || self.country != other.country
+                    || self.locale_script != other.locale_script
+                    || self.locale_variant != other.locale_variant
+                    || self.locale_numbering_system != other.locale_numbering_system,
+                Self::CONFIG_LOCALE,
+            ),
+        ];
+        differences
+            .iter()
+            .filter(|(differs, _)| *differs)
+            .fold(0, |diff, (_, bit)| diff | bit)
+    }
+
+    /// The number of bytes of the known fields that are encoded
+    pub(crate) fn known_size(&self) -> usize {
+        (self.size as usize).min(Self::SIZE)
+    }
+
+    /// The number of bytes this config is encoded into
+    pub(crate) fn encoded_size(&self) -> usize {
+        self.known_size() + self.trailing.len()
+    }
+
+    /// return the language code, e.g. `en` or `fil`.
+    /// An empty string means any language
+    pub fn language_code(&self) -> String {
+        unpack_language_or_region(self.language, b'a')
+    }
+
+    /// Set the language code, which has 2 or 3 lower case letters
+    pub fn set_language_code(&mut self, language: &str) {
+        self.language = pack_language_or_region(language, b'a');
+    }
+
+    /// return the region code, e.g. `US` or `419`.
+    /// An empty string means any region
+    pub fn region_code(&self) -> String {
+        unpack_language_or_region(self.country, b'0')
+    }
+
+    /// Set the region code, which has 2 upper case letters or 3 digits
+    pub fn set_region_code(&mut self, region: &str) {
+        self.country = pack_language_or_region(region, b'0');
+    }
+
+    /// return the locale script, e.g. `Latn`
+    pub fn script(&self) -> String {
+        string_from_bytes(&self.locale_script)
+    }
+
+    /// return the locale variant, e.g. `POSIX`
+    pub fn variant(&self) -> String {
+        string_from_bytes(&self.locale_variant)
+    }
+
+    /// return the locale numbering system, e.g. `latn`
+    pub fn numbering_system(&self) -> String {
+        string_from_bytes(&self.locale_numbering_system)
+    }
+}
+
+impl ResTableConfig {
+    /// The locale qualifier, either in the legacy form `en-rUS`,
+    /// or in the BCP 47 form `b+sr+Latn` when there are script, variant or numbering system
+    fn locale_qualifier(&self) -> Option<String> {
+        if self.language[0] == 0 {
+            return None;
+        }
+        let script_provided = self.locale_script[0] != 0 && self.locale_script_was_computed == 0;
+        let variant = self.variant();
+        let numbering_system = self.numbering_system();
+        if !script_provided && variant.is_empty() && numbering_system.is_empty() {
+            let mut qualifier = self.language_code();
+            if self.country[0] != 0 {
+                qualifier.push_str("-r");
+                qualifier.push_str(&self.region_code());
+            }
+            return Some(qualifier);
+        }
+        let mut qualifier = format!("b+{}", self.language_code());
+        if script_provided {
+            qualifier.push('+');
+            qualifier.push_str(&self.script());
+        }
+        if self.country[0] != 0 {
+            qualifier.push('+');
+            qualifier.push_str(&self.region_code());
+        }
+        if !variant.is_empty() {
+            qualifier.push('+');
+            qualifier.push_str(&variant);
+        }
+        if !numbering_system.is_empty() {
+            qualifier.push_str("+u+nu+");
+            qualifier.push_str(&numbering_system);
+        }
+        Some(qualifier)
+    }
+}
+
+/// Format the config as an aapt qualifier string, e.g. `en-rUS-land-xhdpi-v21`.
+/// The default config is formatted as an empty string
+impl Display for ResTableConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut qualifiers = Vec::new();
+        if self.mcc != 0 {
+            qualifiers.push(format!("mcc{}", self.mcc));
+        }
+        if self.mnc == Self::MNC_ZERO {
+            qualifiers.push("mnc00".to_string());
+        } else if self.mnc != 0 {
+            qualifiers.push(format!("mnc{}", self.mnc));
+        }
+        qualifiers.extend(self.locale_qualifier());
+        let masked_qualifiers = [
+            (
+                self.grammatical_inflection,
+                Self::MASK_GRAMMATICAL_GENDER,
+                GRAMMATICAL_GENDERS,
+                "grammaticalGender",
+            ),
+            (
+                self.screen_layout,
+                Self::MASK_LAYOUTDIR,
+                LAYOUT_DIRS,
+                "layoutDir",
+            ),
+        ];
+        push_masked_qualifiers(&mut qualifiers, &masked_qualifiers);
+        if self.smallest_screen_width_dp != 0 {
+            qualifiers.push(format!("sw{}dp", self.smallest_screen_width_dp));
+        }
+        if self.screen_width_dp != 0 {
+            qualifiers.push(format!("w{}dp", self.screen_width_dp));
+        }
+        if self.screen_height_dp != 0 {
+            qualifiers.push(format!("h{}dp", self.screen_height_dp));
+        }
+        let masked_qualifiers = [
+            (
+                self.screen_layout,
+                Self::MASK_SCREENSIZE,
+                SCREEN_SIZES,
+                "screenLayoutSize",
+            ),
+            (
+                self.screen_layout,
+                Self::MASK_SCREENLONG,
+                SCREEN_LONGS,
+                "screenLayoutLong",
+            ),
+            (
+                self.screen_layout2,
+                Self::MASK_SCREENROUND,
+                SCREEN_ROUNDS,
+                "screenRound",
+            ),
+            (
+                self.color_mode,
+                Self::MASK_WIDE_COLOR_GAMUT,
+                WIDE_COLOR_GAMUTS,
+                "wideColorGamut",
+            ),
+            (self.color_mode, Self::MASK_HDR, HDRS, "hdr"),
+            (self.orientation, u8::MAX, ORIENTATIONS, "orientation"),
+            (
+                self.ui_mode,
+                Self::MASK_UI_MODE_TYPE,
+                UI_MODE_TYPES,
+                "uiModeType",
+            ),
+            (
+                self.ui_mode,
+                Self::MASK_UI_MODE_NIGHT,
+                UI_MODE_NIGHTS,
+                "uiModeNight",
+            ),
+        ];
+        push_masked_qualifiers(&mut qualifiers, &masked_qualifiers);
+        if self.density != Self::DENSITY_DEFAULT {
+            qualifiers.push(
+                DENSITIES
+                    .iter()
+                    .find(|(density, _)| *density == self.density)
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_else(|| format!("{}dpi", self.density)),
+            );
+        }
+        let masked_qualifiers = [
+            (self.touchscreen, u8::MAX, TOUCHSCREENS, "touchscreen"),
+            (
+                self.input_flags,
+                Self::MASK_KEYSHIDDEN,
+                KEYS_HIDDEN,
+                "keysHidden",
+            ),
+            (self.keyboard, u8::MAX, KEYBOARDS, "keyboard"),
+            (
+                self.input_flags,
+                Self::MASK_NAVHIDDEN,
+                NAV_HIDDEN,
+                "inputFlagsNavHidden",
+            ),
+            (self.navigation, u8::MAX, NAVIGATIONS, "navigation"),
+        ];
+        push_masked_qualifiers(&mut qualifiers, &masked_qualifiers);
+        if self.screen_width != 0 || self.screen_height != 0 {
+            qualifiers.push(format!("{}x{}", self.screen_width, self.screen_height));
+        }
+        if self.sdk_version != 0 {
+            qualifiers.push(match self.minor_version {
+                0 => format!("v{}", self.sdk_version),
+                minor_version => format!("v{}.{minor_version}", self.sdk_version),
+            });
+        }
+        write!(f, "{}", qualifiers.join("-"))
+    }
+}
+
+/// Parse an aapt qualifier string, e.g. `en-rUS-land-xhdpi-v21`.
+/// The qualifiers must follow the aapt ordering, and unlike aapt,
+/// no `sdk_version` is implied by the qualifiers
+impl FromStr for ResTableConfig {
+    type Err = ParseConfigError;
+
+    fn from_str(qualifiers: &str) -> Result<Self, Self::Err> {
+        let mut config = ResTableConfig::default();
+        if qualifiers.is_empty() {
+            return Ok(config);
+        }
+        let mut next_parser = 0;
+        for qualifier in qualifiers.split('-') {
+            let parsed = QUALIFIER_PARSERS[next_parser..]
+                .iter()
+                .position(|parse| parse(&mut config, qualifier))
+                .ok_or_else(|| ParseConfigError {
+                    qualifier: qualifier.to_string(),
+                })?;
+            next_parser += parsed + 1;
+        }
+        Ok(config)
+    }
+}
+
+/// The error returned when a qualifier string cannot be parsed into `ResTableConfig`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConfigError {
+    /// The qualifier that is unknown or out of order
+    pub qualifier: String,
+}
+
+impl Display for ParseConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid config qualifier `{}`", self.qualifier)
+    }
+}
+
+impl std::error::Error for ParseConfigError {}
+
+/// Qualifier names of the masked values, in the form of `(value, name)`
+type QualifierNames = &'static [(u8, &'static str)];
+
+const GRAMMATICAL_GENDERS: QualifierNames = &[
+    (ResTableConfig::GRAMMATICAL_GENDER_MASCULINE, "masculine"),
+    (ResTableConfig::GRAMMATICAL_GENDER_FEMININE, "feminine"),
+    (ResTableConfig::GRAMMATICAL_GENDER_NEUTER, "neuter"),
+];
+const LAYOUT_DIRS: QualifierNames = &[
+    (ResTableConfig::LAYOUTDIR_LTR, "ldltr"),
+    (ResTableConfig::LAYOUTDIR_RTL, "ldrtl"),
+];
+const SCREEN_SIZES: QualifierNames = &[
+    (ResTableConfig::SCREENSIZE_SMALL, "small"),
+    (ResTableConfig::SCREENSIZE_NORMAL, "normal"),
+    (ResTableConfig::SCREENSIZE_LARGE, "large"),
+    (ResTableConfig::SCREENSIZE_XLARGE, "xlarge"),
+];
+const SCREEN_LONGS: QualifierNames = &[
+    (ResTableConfig::SCREENLONG_YES, "long"),
+    (ResTableConfig::SCREENLONG_NO, "notlong"),
+];
+const SCREEN_ROUNDS: QualifierNames = &[
+    (ResTableConfig::SCREENROUND_YES, "round"),
+    (ResTableConfig::SCREENROUND_NO, "notround"),
+];
+const WIDE_COLOR_GAMUTS: QualifierNames = &[
+    (ResTableConfig::WIDE_COLOR_GAMUT_YES, "widecg"),
+    (ResTableConfig::WIDE_COLOR_GAMUT_NO, "nowidecg"),
+];
+const HDRS: QualifierNames = &[
+    (ResTableConfig::HDR_YES, "highdr"),
+    (ResTableConfig::HDR_NO, "lowdr"),
+];
+const ORIENTATIONS: QualifierNames = &[
+    (ResTableConfig::ORIENTATION_PORT, "port"),
+    (ResTableConfig::ORIENTATION_LAND, "land"),
+    (ResTableConfig::ORIENTATION_SQUARE, "square"),
+];
+const UI_MODE_TYPES: QualifierNames = &[
+    (ResTableConfig::UI_MODE_TYPE_DESK, "desk"),
+    (ResTableConfig::UI_MODE_TYPE_CAR, "car"),
+    (ResTableConfig::UI_MODE_TYPE_TELEVISION, "television"),
+    (ResTableConfig::UI_MODE_TYPE_APPLIANCE, "appliance"),
+    (ResTableConfig::UI_MODE_TYPE_WATCH, "watch"),
+    (ResTableConfig::UI_MODE_TYPE_VR_HEADSET, "vrheadset"),
+];
+const UI_MODE_NIGHTS: QualifierNames = &[
+    (ResTableConfig::UI_MODE_NIGHT_YES, "night"),
+    (ResTableConfig::UI_MODE_NIGHT_NO, "notnight"),
+];
+const TOUCHSCREENS: QualifierNames = &[
+    (ResTableConfig::TOUCHSCREEN_NOTOUCH, "notouch"),
+    (ResTableConfig::TOUCHSCREEN_STYLUS, "stylus"),
+    (ResTableConfig::TOUCHSCREEN_FINGER, "finger"),
+];
+const KEYS_HIDDEN: QualifierNames = &[
+    (ResTableConfig::KEYSHIDDEN_NO, "keysexposed"),
+    (ResTableConfig::KEYSHIDDEN_YES, "keyshidden"),
+    (ResTableConfig::KEYSHIDDEN_SOFT, "keyssoft"),
+];
+const KEYBOARDS: QualifierNames = &[
+    (ResTableConfig::KEYBOARD_NOKEYS, "nokeys"),
+    (ResTableConfig::KEYBOARD_QWERTY, "qwerty"),
+    (ResTableConfig::KEYBOARD_12KEY, "12key"),
+];
+const NAV_HIDDEN: QualifierNames = &[
+    (ResTableConfig::NAVHIDDEN_NO, "navexposed"),
+    (ResTableConfig::NAVHIDDEN_YES, "navhidden"),
+];
+const NAVIGATIONS: QualifierNames = &[
+    (ResTableConfig::NAVIGATION_NONAV, "nonav"),
+    (ResTableConfig::NAVIGATION_DPAD, "dpad"),
+    (ResTableConfig::NAVIGATION_TRACKBALL, "trackball"),
+    (ResTableConfig::NAVIGATION_WHEEL, "wheel"),
+];
+const DENSITIES: &[(u16, &str)] = &[
+    (ResTableConfig::DENSITY_LOW, "ldpi"),
+    (ResTableConfig::DENSITY_MEDIUM, "mdpi"),
+    (ResTableConfig::DENSITY_TV, "tvdpi"),
+    (ResTableConfig::DENSITY_HIGH, "hdpi"),
+    (ResTableConfig::DENSITY_XHIGH, "xhdpi"),
+    (ResTableConfig::DENSITY_XXHIGH, "xxhdpi"),
+    (ResTableConfig::DENSITY_XXXHIGH, "xxxhdpi"),
+    (ResTableConfig::DENSITY_ANY, "anydpi"),
+    (ResTableConfig::DENSITY_NONE, "nodpi"),
+];
+
+/// Push the names of masked values that are set.
+/// Values without names are formatted as `field=value`
+///
+/// # Arguments:
+/// * qualifiers - the qualifiers that the names are pushed to
+/// * masked_values - tuples of the field, the mask, the names and the field name
+fn push_masked_qualifiers(
+    qualifiers: &mut Vec<String>,
+    masked_values: &[(u8, u8, QualifierNames, &str)],
+) {
+    for (field, mask, names, field_name) in masked_values {
+        let value = field & mask;
+        if value == 0 {
+            continue;
+        }
+        qualifiers.push(
+            names
+                .iter()
+                .find(|(named_value, _)| *named_value == value)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| format!("{field_name}={value}")),
+        );
+    }
+}
+
+/// A parser for one kind of qualifiers.
+/// It returns true and updates the config if the qualifier is of its kind
+type QualifierParser = fn(&mut ResTableConfig, &str) -> bool;
+
+/// The qualifier parsers in the aapt ordering
+const QUALIFIER_PARSERS: &[QualifierParser] = &[
+    |config, qualifier| match parse_number(qualifier, "mcc", "") {
+        Some(mcc) if qualifier.len() == 6 => {
+            config.mcc = mcc;
+            true
+        }
+        _ => false,
+    },
+    |config, qualifier| match parse_number::<u16>(qualifier, "mnc", "") {
+        Some(mnc) if qualifier.len() <= 6 => {
+            config.mnc = if mnc == 0 {
+                ResTableConfig::MNC_ZERO
+            } else {
+                mnc
+            };
+            true
+        }
+        _ => false,
+    },
+    parse_language,
+    |config, qualifier| {
+        let region = match qualifier.strip_prefix('r') {
+            Some(region) if config.language[0] != 0 && is_region(region) => region,
+            _ => return false,
+        };
+        config.set_region_code(&region.to_ascii_uppercase());
+        true
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.grammatical_inflection,
+            ResTableConfig::MASK_GRAMMATICAL_GENDER,
+            GRAMMATICAL_GENDERS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.screen_layout,
+            ResTableConfig::MASK_LAYOUTDIR,
+            LAYOUT_DIRS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_number_into(&mut config.smallest_screen_width_dp, qualifier, "sw", "dp")
+    },
+    |config, qualifier| parse_number_into(&mut config.screen_width_dp, qualifier, "w", "dp"),
+    |config, qualifier| parse_number_into(&mut config.screen_height_dp, qualifier, "h", "dp"),
+    |config, qualifier| {
+        parse_masked(
+            &mut config.screen_layout,
+            ResTableConfig::MASK_SCREENSIZE,
+            SCREEN_SIZES,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.screen_layout,
+            ResTableConfig::MASK_SCREENLONG,
+            SCREEN_LONGS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.screen_layout2,
+            ResTableConfig::MASK_SCREENROUND,
+            SCREEN_ROUNDS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.color_mode,
+            ResTableConfig::MASK_WIDE_COLOR_GAMUT,
+            WIDE_COLOR_GAMUTS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.color_mode,
+            ResTableConfig::MASK_HDR,
+            HDRS,
+            qualifier,
+        )
+    },
+    |config, qualifier| parse_masked(&mut config.orientation, u8::MAX, ORIENTATIONS, qualifier),
+    |config, qualifier| {
+        parse_masked(
+            &mut config.ui_mode,
+            ResTableConfig::MASK_UI_MODE_TYPE,
+            UI_MODE_TYPES,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        parse_masked(
+            &mut config.ui_mode,
+            ResTableConfig::MASK_UI_MODE_NIGHT,
+            UI_MODE_NIGHTS,
+            qualifier,
+        )
+    },
+    |config, qualifier| {
+        let density = DENSITIES
+            .iter()
+            .find(|(_, name)| *name == qualifier)
+            .map(|(density, _)| *density)
+            .or_else(|| parse_number(qualifier, "", "dpi"));
+        match density {
+            Some(density) => {
+                config.density = density;
+                true
+            }
+            None => false,
+        }
+    },
+    |config, qualifier| parse_masked(&mut config.touchscreen, u8::MAX, TOUCHSCREENS, qualifier),
+    |config, qualifier| {
+        parse_masked(
+            &mut config.input_flags,
+            ResTableConfig::MASK_KEYSHIDDEN,
+            KEYS_HIDDEN,
+            qualifier,
+        )
+    },
+    |config, qualifier| parse_masked(&mut config.keyboard, u8::MAX, KEYBOARDS, qualifier),
+    |config, qualifier| {
+        parse_masked(
+            &mut config.input_flags,
+            ResTableConfig::MASK_NAVHIDDEN,
+            NAV_HIDDEN,
+            qualifier,
+        )
+    },
+    |config, qualifier| parse_masked(&mut config.navigation, u8::MAX, NAVIGATIONS, qualifier),
+    |config, qualifier| {
+        let size = qualifier.split_once('x').and_then(|(width, height)| {
+            Some((parse_number(width, "", "")?, parse_number(height, "", "")?))
+        });
+        match size {
+            Some((width, height)) => {
+                config.screen_width = width;
+                config.screen_height = height;
+                true
+            }
+            None => false,
+        }
+    },
+    |config, qualifier| {
+        let (sdk_version, minor_version) = match qualifier.split_once('.') {
+            Some((sdk_version, minor_version)) => (sdk_version, Some(minor_version)),
+            None => (qualifier, None),
+        };
+        let sdk_version = parse_number(sdk_version, "v", "");
+        let minor_version = minor_version.map(|minor| parse_number(minor, "", ""));
+        match (sdk_version, minor_version) {
+            (Some(sdk_version), None) | (Some(sdk_version), Some(Some(_))) => {
+                config.sdk_version = sdk_version;
+                config.minor_version = minor_version.flatten().unwrap_or_default();
+                true
+            }
+            _ => false,
+        }
+    },
+];
+
+/// Parse the language, either as 2 or 3 letters, or in the BCP 47 form of `b+sr+Latn`
+fn parse_language(config: &mut ResTableConfig, qualifier: &str) -> bool {
+    let tag = match qualifier
+        .strip_prefix("b+")
+        .or_else(|| qualifier.strip_prefix("B+"))
+    {
+        Some(tag) => tag,
+        None => {
+            // `car` is the ui mode type instead of a language
+            if !is_language(qualifier) || qualifier.eq_ignore_ascii_case("car") {
+                return false;
+            }
+            config.set_language_code(&qualifier.to_ascii_lowercase());
+            return true;
+        }
+    };
+    let mut subtags = tag.split('+');
+    let language = subtags.next().unwrap_or_default();
+    if !is_language(language) {
+        return false;
+    }
+    let mut locale = config.clone();
+    locale.set_language_code(&language.to_ascii_lowercase());
+    while let Some(subtag) = subtags.next() {
+        let length = subtag.len();
+        if subtag.eq_ignore_ascii_case("u") {
+            match (subtags.next(), subtags.next()) {
+                (Some(key), Some(numbering_system))
+                    if key.eq_ignore_ascii_case("nu")
+                        && (3..=8).contains(&numbering_system.len()) =>
+                {
+                    locale.locale_numbering_system =
+                        bytes_from_str(&numbering_system.to_ascii_lowercase());
+                }
+                _ => return false,
+            }
+        } else if length == 4 && subtag.starts_with(|char: char| char.is_ascii_alphabetic()) {
+            let mut script = subtag.to_ascii_lowercase();
+            script[..1].make_ascii_uppercase();
+            locale.locale_script = bytes_from_str(&script);
+        } else if is_region(subtag) {
+            locale.set_region_code(&subtag.to_ascii_uppercase());
+        } else if (5..=8).contains(&length) || length == 4 {
+            locale.locale_variant = bytes_from_str(subtag);
+        } else {
+            return false;
+        }
+    }
+    *config = locale;
+    true
+}
+
+fn is_language(language: &str) -> bool {
+    (2..=3).contains(&language.len()) && language.chars().all(|char| char.is_ascii_alphabetic())
+}
+
+fn is_region(region: &str) -> bool {
+    match region.len() {
+        2 => region.chars().all(|char| char.is_ascii_alphabetic()),
+        3 => region.chars().all(|char| char.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+/// Set the masked bits of `field` to the value named `qualifier`
+fn parse_masked(field: &mut u8, mask: u8, names: QualifierNames, qualifier: &str) -> bool {
+    match names.iter().find(|(_, name)| *name == qualifier) {
+        Some((value, _)) => {
+            *field = (*field & !mask) | value;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parse a decimal number between `prefix` and `suffix`
+fn parse_number<N: FromStr>(qualifier: &str, prefix: &str, suffix: &str) -> Option<N> {
+    let number = qualifier.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    if number.is_empty() || !number.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok()
+}
+
+fn parse_number_into(field: &mut u16, qualifier: &str, prefix: &str, suffix: &str) -> bool {
+    match parse_number(qualifier, prefix, suffix) {
+        Some(number) => {
+            *field = number;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Encode a string into 0-padded ascii bytes
+fn bytes_from_str<const N: usize>(string: &str) -> [u8; N] {
+    let mut bytes = [0_u8; N];
+    bytes
+        .iter_mut()
+        .zip(string.bytes())
+        .for_each(|(byte, char)| *byte = char);
+    bytes
+}
+
+/// Decode a language or region code. 3 letters codes are packed into 2 bytes
+/// with 5 bits per letter, starting from `base`
+fn unpack_language_or_region(packed: [u8; 2], base: u8) -> String {
+    if packed[0] & 0x80 != 0 {
+        let first = packed[1] & 0x1F;
+        let second = ((packed[1] & 0xE0) >> 5) + ((packed[0] & 0x03) << 3);
+        let third = (packed[0] & 0x7C) >> 2;
+        [first, second, third]
+            .iter()
+            .map(|letter| (letter + base) as char)
+            .collect()
+    } else {
+        string_from_bytes(&packed)
+    }
+}
+
+/// Encode a language or region code, the reverse of `unpack_language_or_region`
+fn pack_language_or_region(code: &str, base: u8) -> [u8; 2] {
+    match code.as_bytes() {
+        [first, second, third] => {
+            let first = first.wrapping_sub(base) & 0x7F;
+            let second = second.wrapping_sub(base) & 0x7F;
+            let third = third.wrapping_sub(base) & 0x7F;
+            [0x80 | (third << 2) | (second >> 3), (second << 5) | first]
+        }
+        [first, second] => [*first, *second],
+        _ => [0, 0],
+    }
+}
+
+/// Decode 0-padded ascii bytes
+fn string_from_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|byte| **byte != 0)
+        .map(|byte| *byte as char)
+        .collect()
+}
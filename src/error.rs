@@ -0,0 +1,162 @@
+use crate::ResourceType;
+use std::fmt::{Display, Formatter};
+
+/// A specialized `Result` type for parsing and writing arsc files
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Location describes where an error happened in the arsc data
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// The number of bytes from the start of the arsc data
+    pub offset: u64,
+    /// The chunks containing the error, from the outermost to the innermost.
+    /// Each chunk is named after its type and its index in the parent chunk,
+    /// e.g. `["Table", "TablePackage[1]", "TableType[5]"]`
+    pub path: Vec<String>,
+}
+
+impl Location {
+    pub(crate) fn at(offset: u64) -> Self {
+        Location {
+            offset,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "offset {:#x}", self.offset)?;
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path.join("/"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors that can happen when parsing or writing arsc files
+#[derive(Debug)]
+pub enum Error {
+    /// Errors from the underlying reader or writer
+    Io(std::io::Error),
+    /// The chunk type is not known by this crate
+    UnknownChunkType {
+        resource_type: u16,
+        location: Location,
+    },
+    /// The header of a chunk is malformed or not expected at its position,
+    /// or the content of a chunk is inconsistent, e.g. unsorted resources
+    BadHeader { reason: String, location: Location },
+    /// The data ends before a chunk is fully read
+    Truncated { location: Location },
+    /// A string cannot be decoded with its encoding
+    InvalidStringEncoding { location: Location },
+    /// A value is too large to fit in its field when being written
+    Overflow {
+        /// The type of the field, e.g. `u16`
+        target: &'static str,
+        location: Location,
+    },
+}
+
+impl Error {
+    /// return the location where the error happened, if it is related to the arsc data
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Error::Io(_) => None,
+            Error::UnknownChunkType { location, .. }
+            | Error::BadHeader { location, .. }
+            | Error::Truncated { location }
+            | Error::InvalidStringEncoding { location }
+            | Error::Overflow { location, .. } => Some(location),
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Error::Io(_) => None,
+            Error::UnknownChunkType { location, .. }
+            | Error::BadHeader { location, .. }
+            | Error::Truncated { location }
+            | Error::InvalidStringEncoding { location }
+            | Error::Overflow { location, .. } => Some(location),
+        }
+    }
+
+    pub(crate) fn bad_header<S: Into<String>>(reason: S, offset: u64) -> Self {
+        Error::BadHeader {
+            reason: reason.into(),
+            location: Location::at(offset),
+        }
+    }
+
+    /// Record that the error happened inside the chunk named `chunk`
+    pub(crate) fn within<S: Into<String>>(mut self, chunk: S) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.path.insert(0, chunk.into());
+        }
+        self
+    }
+
+    /// Set the offset where the error happened
+    pub(crate) fn at_offset(mut self, offset: u64) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.offset = offset;
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "io error: {error}"),
+            Error::UnknownChunkType {
+                resource_type,
+                location,
+            } => write!(f, "unknown chunk type {resource_type:#06x} at {location}"),
+            Error::BadHeader { reason, location } => {
+                write!(f, "bad header at {location}: {reason}")
+            }
+            Error::Truncated { location } => write!(f, "data truncated at {location}"),
+            Error::InvalidStringEncoding { location } => {
+                write!(f, "invalid string encoding at {location}")
+            }
+            Error::Overflow { target, location } => {
+                write!(f, "value cannot fit in {target} at {location}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            Error::Truncated { .. } => {
+                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, error)
+            }
+            error => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+/// The name of a chunk in `Location::path`
+pub(crate) fn chunk_name(resource_type: ResourceType, index: usize) -> String {
+    format!("{resource_type:?}[{index}]")
+}
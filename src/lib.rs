@@ -17,10 +17,30 @@ use std::fs::File;
 use std::io::{Read, Seek, Write};
 use std::path::Path;
 
+mod attr;
+mod bag;
+mod builder;
+pub mod complex;
 pub mod components;
+pub mod config;
+mod error;
+mod lookup;
+mod markup;
 mod parser;
+mod resolve;
+mod style;
 mod writer;
+pub use attr::{AttrDefinition, AttrFormat, AttrSymbol};
+pub use bag::Quantity;
+pub use builder::{ArscBuilder, PackageBuilder};
+pub use complex::*;
 pub use components::*;
+pub use config::*;
+pub use error::{Error, Location, Result};
+pub use lookup::{ParseResourceNameError, ResourceName, ResourceView};
+pub use markup::ParseMarkupError;
+pub use resolve::{ResolveError, ResolvedValue};
+pub use style::{ResolvedStyle, StyleAttribute, Theme};
 
 /// Parse an arsc file into structured data
 ///
@@ -30,7 +50,8 @@ pub use components::*;
 /// a parsed arsc struct
 /// # Error:
 /// * io errors
-pub fn parse<P: AsRef<Path>>(path: P) -> std::io::Result<components::Arsc> {
+/// * errors describing where the arsc file is malformed
+pub fn parse<P: AsRef<Path>>(path: P) -> Result<components::Arsc> {
     parser::parse(File::open(path)?)
 }
 
@@ -42,7 +63,8 @@ pub fn parse<P: AsRef<Path>>(path: P) -> std::io::Result<components::Arsc> {
 /// a parsed arsc struct
 /// # Error:
 /// * io errors
-pub fn parse_from<R: Read + Seek>(reader: R) -> std::io::Result<components::Arsc> {
+/// * errors describing where the arsc data is malformed
+pub fn parse_from<R: Read + Seek>(reader: R) -> Result<components::Arsc> {
     parser::parse(reader)
 }
 
@@ -56,7 +78,8 @@ pub fn parse_from<R: Read + Seek>(reader: R) -> std::io::Result<components::Arsc
 /// the number of bytes that have been written
 /// # Error:
 /// * io errors
-pub fn write<P: AsRef<Path>>(arsc: &components::Arsc, output_path: P) -> std::io::Result<usize> {
+/// * overflow errors if a value cannot fit in its field
+pub fn write<P: AsRef<Path>>(arsc: &components::Arsc, output_path: P) -> Result<usize> {
     let mut writer = std::io::BufWriter::new(File::create(output_path)?);
     write_to(arsc, &mut writer)
 }
@@ -71,6 +94,7 @@ pub fn write<P: AsRef<Path>>(arsc: &components::Arsc, output_path: P) -> std::io
 /// the number of bytes that have been written
 /// # Error:
 /// * io errors
-pub fn write_to<W: Write>(arsc: &components::Arsc, output: &mut W) -> std::io::Result<usize> {
+/// * overflow errors if a value cannot fit in its field
+pub fn write_to<W: Write>(arsc: &components::Arsc, output: &mut W) -> Result<usize> {
     writer::write(arsc, output)
 }
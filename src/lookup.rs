@@ -0,0 +1,279 @@
+use crate::{Arsc, Config, Package, ResourceEntry, ResourceId, Type};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+
+/// ResourceView gathers everything about a resource id that is spread across
+/// the package, its type and the configs of the type
+#[derive(Debug, Clone)]
+pub struct ResourceView<'a> {
+    pub id: ResourceId,
+    pub package: &'a Package,
+    pub r#type: &'a Type,
+    /// the name of the type, e.g. `string`
+    pub type_name: &'a str,
+    /// the name of the resource, e.g. `app_name`
+    pub key_name: &'a str,
+    /// the flags of the spec, describing which config changes the resource varies on.
+    /// None if the type has no spec chunk for the resource
+    pub spec_flags: Option<u32>,
+    /// every config that has the resource, with the entry of the resource in that config
+    pub entries: Vec<(&'a Config, &'a ResourceEntry)>,
+}
+
+impl Arsc {
+    /// Find a resource by its id
+    ///
+    /// The type is found at `types[type_id - 1]`, and the entry of every config
+    /// is found by binary search, so a lookup is O(c * log n) for c configs of n entries.
+    ///
+    /// # Argument:
+    /// * id - the resource id
+    /// # Returns:
+    /// a view of the resource, or None if no config has the resource
+    pub fn get(&self, id: ResourceId) -> Option<ResourceView<'_>> {
+        let (package, r#type) = self.find_type(id)?;
+        package.view(r#type, id)
+    }
+
+    /// Find the package and the type that a resource id belongs to
+    pub(crate) fn find_type(&self, id: ResourceId) -> Option<(&Package, &Type)> {
+        let package = self
+            .packages
+            .iter()
+            .find(|package| package.id == id.package() as u32)?;
+        package.find_type(id).map(|r#type| (package, r#type))
+    }
+}
+
+impl Package {
+    /// Find a resource of this package by its id
+    ///
+    /// # Argument:
+    /// * id - the resource id
+    /// # Returns:
+    /// a view of the resource, or None if the id is not in this package or no config has the resource
+    pub fn get(&self, id: ResourceId) -> Option<ResourceView<'_>> {
+        if self.id != id.package() as u32 {
+            return None;
+        }
+        self.view(self.find_type(id)?, id)
+    }
+
+    fn find_type(&self, id: ResourceId) -> Option<&Type> {
+        let type_id = id.type_id() as usize;
+        self.types
+            .get(type_id.checked_sub(1)?)
+            .filter(|r#type| r#type.id == type_id)
+    }
+
+    fn view<'a>(&'a self, r#type: &'a Type, id: ResourceId) -> Option<ResourceView<'a>> {
+        let spec_id = id.entry() as usize;
+        let entries = r#type
+            .configs
+            .iter()
+            .filter_map(|config| config.resources.get(spec_id).map(|entry| (config, entry)))
+            .collect::<Vec<_>>();
+        let (_, first_entry) = entries.first()?;
+        Some(ResourceView {
+            id,
+            package: self,
+            r#type,
+            type_name: self
+                .type_names
+                .strings
+                .get(r#type.id - 1)
+                .map_or("", String::as_str),
+            key_name: self
+                .key_names
+                .strings
+                .get(first_entry.name_index)
+                .map_or("", String::as_str),
+            spec_flags: r#type
+                .specs
+                .as_ref()
+                .and_then(|specs| specs.specs.get(spec_id))
+                .map(|spec| spec.flags),
+            entries,
+        })
+    }
+}
+
+/// ResourceName is the qualified name of a resource, e.g. `com.example:string/app_name`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceName {
+    /// the name of the package, or None to look up in every package
+    pub package: Option<String>,
+    pub r#type: String,
+    pub name: String,
+}
+
+impl Display for ResourceName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(package) = &self.package {
+            write!(f, "{package}:")?;
+        }
+        write!(f, "{}/{}", self.r#type, self.name)
+    }
+}
+
+impl FromStr for ResourceName {
+    type Err = ParseResourceNameError;
+
+    /// Parse a name in the form of `[package:]type/name`, or in the reference syntax of aapt,
+    /// such as `@string/app_name`, `@android:string/ok` and `?android:textColor`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseResourceNameError {
+            name: s.to_string(),
+        };
+        let (is_attribute, name) = if let Some(name) = s.strip_prefix('?') {
+            (true, name)
+        } else if let Some(name) = s.strip_prefix('@') {
+            // `@+id/name` creates an id, `@*android:string/name` refers to a private resource
+            let name = name.strip_prefix('+').unwrap_or(name);
+            (false, name.strip_prefix('*').unwrap_or(name))
+        } else {
+            (false, s)
+        };
+        let (package, name) = match name.split_once(':') {
+            Some((package, name)) if !package.is_empty() => (Some(package.to_string()), name),
+            Some(_) => return Err(error()),
+            None => (None, name),
+        };
+        let (r#type, name) = match name.split_once('/') {
+            Some((r#type, name)) => (r#type, name),
+            // the type of a theme attribute reference can be omitted
+            None if is_attribute => ("attr", name),
+            None => return Err(error()),
+        };
+        if r#type.is_empty() || name.is_empty() || name.contains('/') {
+            return Err(error());
+        }
+        Ok(ResourceName {
+            package,
+            r#type: r#type.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// The error when a string is not a valid resource name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseResourceNameError {
+    pub name: String,
+}
+
+impl Display for ParseResourceNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid resource name `{}`", self.name)
+    }
+}
+
+impl std::error::Error for ParseResourceNameError {}
+
+/// NameIndex maps the type names and key names of a package to resource ids.
+/// It is built on the first lookup by name, and dropped when the names or the types
+/// of the package are accessed mutably
+#[derive(Default)]
+pub(crate) struct NameIndex(Mutex<Option<HashMap<String, HashMap<String, ResourceId>>>>);
+
+impl NameIndex {
+    pub(crate) fn clear(&mut self) {
+        *self.0.get_mut().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+impl Debug for NameIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NameIndex")
+    }
+}
+
+impl Arsc {
+    /// Find the id of a resource by its name
+    ///
+    /// The names of a package are indexed on the first lookup,
+    /// so later lookups take O(1) until the package is modified.
+    ///
+    /// # Argument:
+    /// * name - the name of the resource. If it has no package, the packages are searched in order
+    /// # Returns:
+    /// the id of the first resource with the name, or None if there is no such resource
+    pub fn id_of(&self, name: &ResourceName) -> Option<ResourceId> {
+        self.packages
+            .iter()
+            .filter(|package| name.package.iter().all(|it| *it == package.name))
+            .find_map(|package| package.find_id(&name.r#type, &name.name))
+    }
+
+    /// Find a resource by its name
+    ///
+    /// # Argument:
+    /// * name - the name of the resource, e.g. `com.example:string/app_name` or `@string/app_name`
+    /// # Returns:
+    /// a view of the resource, or None if the name is invalid or there is no such resource
+    pub fn get_by_name(&self, name: &str) -> Option<ResourceView<'_>> {
+        self.get(self.id_of(&name.parse().ok()?)?)
+    }
+}
+
+impl Package {
+    /// Find the id of a resource in this package by its name
+    ///
+    /// # Argument:
+    /// * name - the name of the resource. If it has a package, it must be the name of this package
+    /// # Returns:
+    /// the id of the resource, or None if there is no such resource in this package
+    pub fn id_of(&self, name: &ResourceName) -> Option<ResourceId> {
+        if name.package.iter().any(|it| *it != self.name) {
+            return None;
+        }
+        self.find_id(&name.r#type, &name.name)
+    }
+
+    /// Find a resource of this package by its name
+    ///
+    /// # Argument:
+    /// * name - the name of the resource, e.g. `string/app_name` or `@string/app_name`
+    /// # Returns:
+    /// a view of the resource, or None if the name is invalid or there is no such resource
+    pub fn get_by_name(&self, name: &str) -> Option<ResourceView<'_>> {
+        self.get(self.id_of(&name.parse().ok()?)?)
+    }
+
+    fn find_id(&self, type_name: &str, key_name: &str) -> Option<ResourceId> {
+        let mut index = self
+            .name_index
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        index
+            .get_or_insert_with(|| self.build_name_index())
+            .get(type_name)?
+            .get(key_name)
+            .copied()
+    }
+
+    fn build_name_index(&self) -> HashMap<String, HashMap<String, ResourceId>> {
+        let mut index: HashMap<String, HashMap<String, ResourceId>> = HashMap::new();
+        for r#type in &self.types {
+            let type_name = match self.type_names.strings.get(r#type.id.wrapping_sub(1)) {
+                Some(type_name) => type_name,
+                None => continue,
+            };
+            let keys = index.entry(type_name.clone()).or_default();
+            let entries = r#type
+                .configs
+                .iter()
+                .flat_map(|config| &config.resources.resources);
+            for entry in entries {
+                let key_name = self.key_names.strings.get(entry.name_index);
+                if let (Some(key_name), Some(id)) = (key_name, self.resource_id(r#type, entry)) {
+                    keys.entry(key_name.clone()).or_insert(id);
+                }
+            }
+        }
+        index
+    }
+}
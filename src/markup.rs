@@ -0,0 +1,352 @@
+use crate::{StringPool, Style, StyleSpan};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// The error when a string is not valid markup of a styled string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMarkupError {
+    /// the byte offset in the markup where the error is found
+    pub position: usize,
+    pub reason: &'static str,
+}
+
+impl Display for ParseMarkupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid markup at {}: {}", self.position, self.reason)
+    }
+}
+
+impl std::error::Error for ParseMarkupError {}
+
+impl StringPool {
+    /// Render a string with its style spans into the markup used in resource xml,
+    /// e.g. `Hello <b>world</b>`. The span `font;color=#ff0000` is rendered as
+    /// `<font color="#ff0000">`, and spans that are not nested are split
+    ///
+    /// # Argument:
+    /// * index - the index of the string
+    /// # Returns:
+    /// the markup, or None if the string does not exist or a span has no valid name
+    pub fn render_markup(&self, index: usize) -> Option<String> {
+        let text = self.strings.get(index)?;
+        let spans = match self.styles.get(index) {
+            Some(style) => style
+                .spans
+                .iter()
+                .filter(|span| span.start <= span.end.wrapping_add(1))
+                .map(|span| Some((self.strings.get(span.name as usize)?.as_str(), span)))
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        // outer spans are opened first. Spans are listed in the order their tags are closed,
+        // so the later one is the outer one if two spans cover the same range.
+        // Empty spans are opened before the other spans at their position, in their order
+        let mut spans = spans.into_iter().enumerate().collect::<Vec<_>>();
+        spans.sort_by_key(|(index, (_, span))| {
+            let order = if is_empty(span) {
+                *index as i64
+            } else {
+                -(*index as i64)
+            };
+            (span.start, !is_empty(span), Reverse(span.end), order)
+        });
+        let mut pending = spans.into_iter().map(|(_, span)| span).peekable();
+        let mut opened: Vec<(&str, &StyleSpan)> = Vec::new();
+        let mut markup = String::new();
+        // the position in UTF-16 code units, as the spans are
+        let mut position = 0;
+        for c in text.chars().map(Some).chain(std::iter::once(None)) {
+            if let Some(first_ended) = opened.iter().position(|(_, span)| span.end < position) {
+                let mut reopened = Vec::new();
+                while opened.len() > first_ended {
+                    let (tag, span) = opened.pop()?;
+                    write_closing_tag(&mut markup, tag);
+                    if span.end >= position {
+                        reopened.push((tag, span));
+                    }
+                }
+                for (tag, span) in reopened.into_iter().rev() {
+                    write_opening_tag(&mut markup, tag);
+                    opened.push((tag, span));
+                }
+            }
+            while let Some((tag, span)) = pending.next_if(|(_, span)| span.start <= position) {
+                write_opening_tag(&mut markup, tag);
+                if is_empty(span) {
+                    write_closing_tag(&mut markup, tag);
+                } else {
+                    opened.push((tag, span));
+                }
+            }
+            if let Some(c) = c {
+                escape_into(&mut markup, c, false);
+                position += c.len_utf16() as u32;
+            }
+        }
+        while let Some((tag, _)) = opened.pop() {
+            write_closing_tag(&mut markup, tag);
+        }
+        Some(markup)
+    }
+
+    /// Parse markup into a string with style spans, and append it to the pool.
+    /// The names of the spans are added to the pool if they are not in it yet.
+    ///
+    /// Notice: styles are matched to strings by index, so if the new string has spans,
+    /// empty styles are added for the unstyled strings before it
+    ///
+    /// # Argument:
+    /// * markup - the markup of the string, e.g. `Hello <b>world</b>`
+    /// # Returns:
+    /// the index of the new string
+    pub fn push_markup(&mut self, markup: &str) -> Result<usize, ParseMarkupError> {
+        let (text, spans) = parse_markup(markup)?;
+        let index = self.strings.len();
+        self.strings.push(text);
+        self.set_spans(index, spans);
+        Ok(index)
+    }
+
+    /// Parse markup into a string with style spans, and replace the string at `index` with it.
+    /// The names of the spans are added to the pool if they are not in it yet.
+    ///
+    /// Notice: all the values referring to the string will see the change
+    ///
+    /// # Arguments:
+    /// * index - the index of the string to replace
+    /// * markup - the markup of the string, e.g. `Hello <b>world</b>`
+    /// # Returns:
+    /// an error at position 0 if there is no string at `index`, or the error in the markup
+    pub fn set_markup(&mut self, index: usize, markup: &str) -> Result<(), ParseMarkupError> {
+        if index >= self.strings.len() {
+            return Err(ParseMarkupError {
+                position: 0,
+                reason: "string to replace does not exist",
+            });
+        }
+        let (text, spans) = parse_markup(markup)?;
+        self.strings[index] = text;
+        self.set_spans(index, spans);
+        Ok(())
+    }
+
+    /// Set the spans of the string at `index`, adding their names to the pool.
+    /// Styles are matched to strings by index, so styles are only kept up to the highest
+    /// styled string: an empty style is added for every string before `index` that has none,
+    /// and the trailing empty styles are dropped when the last styled string loses its spans
+    fn set_spans(&mut self, index: usize, spans: Vec<Span>) {
+        if spans.is_empty() {
+            if index + 1 == self.styles.len() {
+                self.styles.pop();
+                while self.styles.last().map(|style| style.spans.is_empty()) == Some(true) {
+                    self.styles.pop();
+                }
+            } else if let Some(style) = self.styles.get_mut(index) {
+                style.spans.clear();
+            }
+            return;
+        }
+        let tags = spans
+            .iter()
+            .map(|(tag, ..)| tag.as_str())
+            .collect::<HashSet<_>>();
+        // the first index of every tag that is already in the pool
+        let mut indices = HashMap::new();
+        for (string_index, string) in self.strings.iter().enumerate() {
+            if tags.contains(string.as_str()) {
+                indices.entry(string.clone()).or_insert(string_index);
+            }
+        }
+        let spans = spans
+            .into_iter()
+            .map(|(tag, start, end)| StyleSpan {
+                name: self.intern(&mut indices, tag) as u32,
+                start,
+                end,
+            })
+            .collect::<Vec<_>>();
+        while self.styles.len() <= index {
+            self.styles.push(Style { spans: Vec::new() });
+        }
+        self.styles[index].spans = spans;
+    }
+}
+
+fn write_opening_tag(markup: &mut String, tag: &str) {
+    let mut parts = tag.split(';');
+    markup.push('<');
+    markup.push_str(parts.next().unwrap_or_default());
+    for attribute in parts {
+        markup.push(' ');
+        match attribute.split_once('=') {
+            Some((name, value)) => {
+                markup.push_str(name);
+                markup.push_str("=\"");
+                value.chars().for_each(|c| escape_into(markup, c, true));
+                markup.push('"');
+            }
+            None => markup.push_str(attribute),
+        }
+    }
+    markup.push('>');
+}
+
+fn write_closing_tag(markup: &mut String, tag: &str) {
+    markup.push_str("</");
+    markup.push_str(tag.split(';').next().unwrap_or_default());
+    markup.push('>');
+}
+
+fn escape_into(markup: &mut String, c: char, in_attribute: bool) {
+    match c {
+        '<' => markup.push_str("&lt;"),
+        // a quoted attribute value can hold `>` as it is
+        '>' if !in_attribute => markup.push_str("&gt;"),
+        '&' => markup.push_str("&amp;"),
+        '"' if in_attribute => markup.push_str("&quot;"),
+        c => markup.push(c),
+    }
+}
+
+/// A span is empty if it ends before it starts, e.g. `<b></b>`
+fn is_empty(span: &StyleSpan) -> bool {
+    span.end.wrapping_add(1) == span.start
+}
+
+/// Find the `>` that closes the tag at the start of `markup`, skipping quoted attribute values
+fn tag_end(markup: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in markup.char_indices() {
+        match quote {
+            Some(opening) if c == opening => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(index),
+            None => {}
+        }
+    }
+    None
+}
+
+/// A span in the form of `(name, start, end)`, where `name` is the tag name
+/// followed by `;name=value` for every attribute
+type Span = (String, u32, u32);
+
+/// Parse markup into the text and the spans, which are listed in the order
+/// their tags are closed like aapt does
+fn parse_markup(markup: &str) -> Result<(String, Vec<Span>), ParseMarkupError> {
+    let mut text = String::new();
+    let mut position = 0_u32;
+    let mut spans = Vec::new();
+    // the tags that are still open, with their span names and start positions
+    let mut opened: Vec<(&str, String, u32)> = Vec::new();
+    let mut rest = markup;
+    while let Some(c) = rest.chars().next() {
+        let offset = markup.len() - rest.len();
+        let error = |reason| ParseMarkupError {
+            position: offset,
+            reason,
+        };
+        match c {
+            '<' => {
+                let end = tag_end(rest).ok_or_else(|| error("tag is not closed"))?;
+                let tag = rest[1..end].trim();
+                rest = &rest[end + 1..];
+                if let Some(name) = tag.strip_prefix('/') {
+                    let name = name.trim();
+                    let index = opened
+                        .iter()
+                        .rposition(|(opened_name, ..)| *opened_name == name)
+                        .ok_or_else(|| error("closing tag has no opening tag"))?;
+                    let (_, span_name, start) = opened.remove(index);
+                    // the end of a span is the last character in it, which is before the start
+                    // for an empty span, like aapt does
+                    spans.push((span_name, start, position.wrapping_sub(1)));
+                } else if !tag.ends_with('/') {
+                    let (name, attributes) = match tag.find(char::is_whitespace) {
+                        Some(end) => (&tag[..end], &tag[end..]),
+                        None => (tag, ""),
+                    };
+                    if name.is_empty() {
+                        return Err(error("tag has no name"));
+                    }
+                    let span_name = parse_attributes(name, attributes)
+                        .ok_or_else(|| error("attributes must be in the form of name=\"value\""))?;
+                    opened.push((name, span_name, position));
+                }
+            }
+            '&' => {
+                let (c, length) = unescape(rest).ok_or_else(|| error("unknown entity"))?;
+                text.push(c);
+                position += c.len_utf16() as u32;
+                rest = &rest[length..];
+            }
+            c => {
+                text.push(c);
+                position += c.len_utf16() as u32;
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !opened.is_empty() {
+        return Err(ParseMarkupError {
+            position: markup.len(),
+            reason: "tag is not closed",
+        });
+    }
+    Ok((text, spans))
+}
+
+/// Turn the attributes of a tag into the span name, e.g. `font;color=#ff0000`
+fn parse_attributes(name: &str, mut attributes: &str) -> Option<String> {
+    let mut span_name = name.to_string();
+    loop {
+        attributes = attributes.trim_start();
+        if attributes.is_empty() {
+            return Some(span_name);
+        }
+        let (attribute, rest) = attributes.split_once('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let (mut value, rest) = rest[1..].split_once(quote)?;
+        span_name.push(';');
+        span_name.push_str(attribute.trim());
+        span_name.push('=');
+        while let Some(c) = value.chars().next() {
+            let (c, length) = match c {
+                '&' => unescape(value)?,
+                c => (c, c.len_utf8()),
+            };
+            span_name.push(c);
+            value = &value[length..];
+        }
+        attributes = rest;
+    }
+}
+
+/// Decode the entity at the start of `text`
+///
+/// # Returns:
+/// the character and the length of the entity in bytes
+fn unescape(text: &str) -> Option<(char, usize)> {
+    let end = text.find(';')?;
+    let c = match &text[1..end] {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        entity => {
+            let code = match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some((c, end + 1))
+}
@@ -1,19 +1,30 @@
 use super::read_util;
 use crate::components::{Header, ResourceType, StringPool, Value};
+use crate::error::{chunk_name, Error, Location};
 use crate::{
-    Arsc, Config, Package, ResourceEntry, ResourceValue, Resources, Spec, Specs, Style, StyleSpan,
-    Type,
+    Arsc, Config, Library, LibraryEntry, Overlayable, OverlayablePolicy, Package, PackageChunk,
+    PolicyFlags, ResTableConfig, ResourceEntry, ResourceId, ResourceValue, Resources, Spec, Specs,
+    StagedAlias, Style, StyleSpan, TableChunk, Type, UnknownChunk,
 };
-use std::io::{BufReader, Error, Read, Seek, SeekFrom};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 
-impl<R: Read> TryFrom<&mut BufReader<R>> for Header {
-    type Error = std::io::Error;
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Header {
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let base = reader.stream_position()?;
         let type_bits = read_util::read_u16(reader)?;
-        let r#type = ResourceType::from(type_bits);
+        let r#type = ResourceType::try_from(type_bits).map_err(|error| error.at_offset(base))?;
         let header_size = read_util::read_u16(reader)?;
         let size = read_util::read_u32(reader)? as u64;
+        if header_size < 8 || size < header_size as u64 {
+            return Err(Error::bad_header(
+                format!("invalid header size {header_size} or chunk size {size}"),
+                base,
+            ));
+        }
         Ok(Header {
             resource_type: r#type,
             header_size,
@@ -22,19 +33,56 @@ impl<R: Read> TryFrom<&mut BufReader<R>> for Header {
     }
 }
 
+impl Header {
+    /// Check the chunk at the position of the reader ends before `parent_end`,
+    /// so its sizes and counts can be trusted. The reader is not advanced
+    fn check_within<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        parent_end: u64,
+    ) -> crate::Result<()> {
+        let start = reader.stream_position()?;
+        let header = Header::try_from(&mut *reader)?;
+        read_util::seek_to(reader, start)?;
+        if start + header.size > parent_end {
+            return Err(Error::bad_header(
+                format!("chunk size {} exceeds its parent", header.size),
+                start,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the chunk has the `expected` type
+    fn expect(&self, expected: ResourceType, offset: u64) -> crate::Result<()> {
+        if self.resource_type != expected {
+            return Err(Error::bad_header(
+                format!("expect {expected:?} chunk, found {:?}", self.resource_type),
+                offset,
+            ));
+        }
+        Ok(())
+    }
+}
+
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for StringPool {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
         let base = reader.stream_position()?;
-        let header = crate::Header::try_from(&mut *reader)?;
-        assert_eq!(header.resource_type, crate::ResourceType::StringPool);
+        let header = Header::try_from(&mut *reader)?;
+        header.expect(ResourceType::StringPool, base)?;
 
         let string_count = read_util::read_u32(reader)? as usize;
         let style_count = read_util::read_u32(reader)? as usize;
         let flags = read_util::read_u32(reader)?;
         let string_offset = read_util::read_u32(reader)? as u64;
         let style_offset = read_util::read_u32(reader)? as u64;
+        if (string_count as u64 + style_count as u64) * 4 > header.size {
+            return Err(Error::bad_header(
+                format!("{string_count} strings and {style_count} styles exceed the chunk size"),
+                base,
+            ));
+        }
         let mut offsets = Vec::with_capacity(string_count);
         for _ in 0..string_count {
             offsets.push(read_util::read_u32(reader)? as u64)
@@ -43,7 +91,7 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for StringPool {
         for _ in 0..style_count {
             style_offsets.push(read_util::read_u32(reader)? as u64)
         }
-        debug_assert_eq!(reader.stream_position()?, string_offset + base);
+        read_util::seek_to(reader, base + string_offset)?;
         let mut strings = Vec::with_capacity(string_count);
         for _ in 0..string_count {
             let string = if flags & StringPool::UTF8_FLAG != 0 {
@@ -56,7 +104,7 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for StringPool {
         reader.seek(SeekFrom::Start(base + style_offset))?;
         let styles = std::iter::repeat_with(|| Style::try_from(&mut *reader))
             .take(style_count)
-            .collect::<std::io::Result<Vec<_>>>()?;
+            .collect::<crate::Result<Vec<_>>>()?;
         reader.seek(SeekFrom::Start(base + header.size))?;
         Ok(StringPool {
             flags,
@@ -83,7 +131,7 @@ impl StringPool {
         Ok(String::from_utf8_lossy(&string_bytes).to_string())
     }
 
-    fn utf8_length<R: Read>(reader: &mut BufReader<R>) -> Result<usize, Error> {
+    fn utf8_length<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<usize, Error> {
         let mut length = read_util::read_u8(reader)? as usize;
         if (length & 0x80) != 0 {
             length = ((length & 0x7F) << 8) | read_util::read_u8(reader)? as usize;
@@ -93,15 +141,14 @@ impl StringPool {
 
     fn read_utf16_string_item<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<String, Error> {
         let char_count = Self::utf16_length(reader)?;
-        let mut string_bytes = Vec::with_capacity(char_count);
-        for _ in 0..char_count {
-            string_bytes.push(read_util::read_u16(reader)?);
-        }
+        let string_bytes = std::iter::repeat_with(|| read_util::read_u16(reader))
+            .take(char_count)
+            .collect::<crate::Result<Vec<_>>>()?;
         reader.seek(SeekFrom::Current(2))?; // skip null terminator
         Ok(String::from_utf16_lossy(&string_bytes))
     }
 
-    fn utf16_length<R: Read>(reader: &mut BufReader<R>) -> Result<usize, Error> {
+    fn utf16_length<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<usize, Error> {
         let mut length = read_util::read_u16(reader)? as usize;
         if length > 0x7FFF {
             length = ((length & 0x7FFF) << 8) | read_util::read_u16(reader)? as usize;
@@ -110,7 +157,7 @@ impl StringPool {
     }
 }
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Style {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
         let mut spans = Vec::new();
@@ -128,7 +175,7 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Style {
 }
 
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Value {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
         let size = read_util::read_u16(reader)?;
@@ -145,7 +192,7 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Value {
 }
 
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Specs {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
         let type_id = read_util::read_u8(reader)? as usize;
@@ -158,7 +205,6 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Specs {
             .enumerate()
             .map(|(id, flags)| Result::Ok(Spec::new(flags?, id)))
             .collect::<Result<Vec<_>, Self::Error>>()?;
-        debug_assert!(!specs.is_empty(), "Specs cannot be empty");
         Ok(Specs {
             type_id,
             res0,
@@ -170,19 +216,22 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Specs {
 }
 
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for ResourceEntry {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
-        let _size = read_util::read_u16(reader)?;
+        let size_or_key = read_util::read_u16(reader)?;
         let flags = read_util::read_u16(reader)?;
+        if flags & ResourceEntry::ENTRY_FLAG_COMPACT != 0 {
+            return ResourceEntry::parse_compact(reader, size_or_key, flags);
+        }
         let name_index = read_util::read_u32(reader)? as usize;
 
         let value = if flags & ResourceEntry::ENTRY_FLAG_COMPLEX != 0 {
-            let parent = read_util::read_u32(reader)?;
+            let parent = ResourceId(read_util::read_u32(reader)?);
             let count = read_util::read_u32(reader)? as usize;
-            let mut values = Vec::with_capacity(count);
+            let mut values = Vec::new();
             for _ in 0..count {
-                let index = read_util::read_u32(reader)?;
+                let index = ResourceId(read_util::read_u32(reader)?);
                 let value = Value::try_from(&mut *reader)?;
                 values.push((index, value));
             }
@@ -199,71 +248,275 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for ResourceEntry {
     }
 }
 
+impl ResourceEntry {
+    /// Compact entries store the key index in place of the size,
+    /// and the value type in the higher 8 bits of the flags
+    fn parse_compact<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        key: u16,
+        flags: u16,
+    ) -> crate::Result<Self> {
+        let data_index = read_util::read_u32(reader)? as usize;
+        let value = Value {
+            size: 8,
+            zero: 0,
+            r#type: (flags >> 8) as u8,
+            data_index,
+        };
+        Ok(ResourceEntry {
+            flags: flags & 0x00FF,
+            name_index: key as usize,
+            value: ResourceValue::Plain(value),
+            spec_id: usize::MAX,
+        })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for ResTableConfig {
+    type Error = Error;
+
+    /// Missing fields of configs shorter than `ResTableConfig::SIZE` are 0
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let size = read_util::read_u32(reader)? as usize;
+        let mut buffer = [0_u8; ResTableConfig::SIZE];
+        buffer[..4].copy_from_slice(&(size as u32).to_le_bytes());
+        let known = size.clamp(4, ResTableConfig::SIZE);
+        read_util::read_bytes(reader, &mut buffer[4..known])?;
+        let trailing = read_util::read_vec(reader, size.saturating_sub(ResTableConfig::SIZE))?;
+
+        let u16_at = |offset: usize| u16::from_le_bytes([buffer[offset], buffer[offset + 1]]);
+        let array_at = |offset: usize| {
+            let mut array = [0_u8; 8];
+            array.copy_from_slice(&buffer[offset..offset + 8]);
+            array
+        };
+        Ok(ResTableConfig {
+            size: size as u32,
+            mcc: u16_at(4),
+            mnc: u16_at(6),
+            language: [buffer[8], buffer[9]],
+            country: [buffer[10], buffer[11]],
+            orientation: buffer[12],
+            touchscreen: buffer[13],
+            density: u16_at(14),
+            keyboard: buffer[16],
+            navigation: buffer[17],
+            input_flags: buffer[18],
+            grammatical_inflection: buffer[19],
+            screen_width: u16_at(20),
+            screen_height: u16_at(22),
+            sdk_version: u16_at(24),
+            minor_version: u16_at(26),
+            screen_layout: buffer[28],
+            ui_mode: buffer[29],
+            smallest_screen_width_dp: u16_at(30),
+            screen_width_dp: u16_at(32),
+            screen_height_dp: u16_at(34),
+            locale_script: [buffer[36], buffer[37], buffer[38], buffer[39]],
+            locale_variant: array_at(40),
+            screen_layout2: buffer[48],
+            color_mode: buffer[49],
+            screen_config_pad2: u16_at(50),
+            locale_script_was_computed: buffer[52],
+            locale_numbering_system: array_at(53),
+            end_padding: [buffer[61], buffer[62], buffer[63]],
+            trailing,
+        })
+    }
+}
+
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Config {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let base = reader.stream_position()?;
+        let header = Header::try_from(&mut *reader)?;
+        header.expect(ResourceType::TableType, base)?;
         let type_id = read_util::read_u8(reader)? as usize;
         let res0 = read_util::read_u8(reader)?;
         let res1 = read_util::read_u16(reader)?;
         let entry_count = read_util::read_u32(reader)? as usize;
-        let _entry_start = read_util::read_u32(reader)?;
-        let config_id = Config::parse_config_id(reader)?;
+        let entry_start = read_util::read_u32(reader)? as u64;
+        if entry_count as u64 * 2 > header.size || entry_start > header.size {
+            return Err(Error::bad_header(
+                format!("{entry_count} entries starting at {entry_start} exceed the chunk size"),
+                base,
+            ));
+        }
+        // the entry id of a resource id has 16 bits
+        if entry_count > u16::MAX as usize + 1 {
+            return Err(Error::bad_header(
+                format!("{entry_count} entries exceed the ids of a type"),
+                base,
+            ));
+        }
+        let config_id = Config::parse_config_id(reader, &header)?;
+        read_util::seek_to(reader, base + header.header_size as u64)?;
 
-        let resources = Config::parse_config_resources(reader, entry_count)?;
+        let (offsets, entry_count) = if res0 & Config::FLAG_SPARSE != 0 {
+            // sparse entries only list the existing resources, so the number of
+            // `missing_entries` cannot be determined until the associated specs are known
+            let offsets = Config::parse_sparse_entry_offsets(reader, entry_count)?;
+            let resource_count = offsets.len();
+            (offsets, resource_count)
+        } else if res0 & Config::FLAG_OFFSET16 != 0 {
+            (
+                Config::parse_offset16_entry_offsets(reader, entry_count)?,
+                entry_count,
+            )
+        } else {
+            (
+                Config::parse_entry_offsets(reader, entry_count)?,
+                entry_count,
+            )
+        };
+        let resources = Config::parse_config_resources(
+            reader,
+            base + entry_start,
+            base + header.size,
+            offsets,
+            entry_count,
+        )?;
         Ok(Config {
             type_id,
             res0,
             res1,
             id: config_id,
             resources,
-            header_size: u16::MAX,
+            header_size: header.header_size,
         })
     }
 }
 
 impl Config {
-    fn parse_config_id<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    fn parse_config_id<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        header: &Header,
+    ) -> crate::Result<ResTableConfig> {
+        let start = reader.stream_position()?;
         let size = read_util::read_u32(reader)? as usize;
-        let mut config_id = vec![0_u8; size];
+        if size < 4 || size + 20 > header.header_size as usize {
+            return Err(Error::bad_header(
+                format!("invalid config size {size}"),
+                start,
+            ));
+        }
         reader.seek(SeekFrom::Current(-4))?;
-        reader.read_exact(&mut config_id)?;
-        Ok(config_id)
+        ResTableConfig::try_from(&mut *reader)
     }
 
-    fn parse_config_resources<R: Read + Seek>(
+    /// Read the offsets of existing entries
+    /// # Returns:
+    /// pairs of spec index and the entry offset relative to the start of entries
+    fn parse_entry_offsets<R: Read + Seek>(
         reader: &mut BufReader<R>,
         entry_count: usize,
-    ) -> std::io::Result<Resources> {
-        let _current = reader.stream_position()?;
-        let entries = std::iter::repeat_with(|| read_util::read_u32(reader))
-            .take(entry_count)
-            .collect::<std::io::Result<Vec<_>>>()?;
-        let _current = reader.stream_position()?;
-        let mut resources = Vec::with_capacity(entry_count);
-        for (spec_index, entry) in entries.into_iter().enumerate() {
-            if entry == u32::MAX {
-                continue;
+    ) -> crate::Result<Vec<(usize, u64)>> {
+        let mut offsets = Vec::with_capacity(entry_count);
+        for spec_index in 0..entry_count {
+            let offset = read_util::read_u32(reader)?;
+            if offset != u32::MAX {
+                offsets.push((spec_index, offset as u64));
             }
-            let mut resource = ResourceEntry::try_from(&mut *reader)?;
+        }
+        Ok(offsets)
+    }
+
+    fn parse_offset16_entry_offsets<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        entry_count: usize,
+    ) -> crate::Result<Vec<(usize, u64)>> {
+        let mut offsets = Vec::with_capacity(entry_count);
+        for spec_index in 0..entry_count {
+            let offset = read_util::read_u16(reader)?;
+            if offset != u16::MAX {
+                offsets.push((spec_index, offset as u64 * 4));
+            }
+        }
+        Ok(offsets)
+    }
+
+    /// Read the sparse entry offsets, whose spec indices must be in ascending order
+    fn parse_sparse_entry_offsets<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        entry_count: usize,
+    ) -> crate::Result<Vec<(usize, u64)>> {
+        let mut offsets = Vec::<(usize, u64)>::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            let start = reader.stream_position()?;
+            let spec_index = read_util::read_u16(reader)? as usize;
+            let offset = read_util::read_u16(reader)? as u64 * 4;
+            if let Some((previous, _)) = offsets
+                .last()
+                .filter(|(previous, _)| *previous >= spec_index)
+            {
+                return Err(Error::bad_header(
+                    format!("sparse entry {spec_index} is not after entry {previous}"),
+                    start,
+                ));
+            }
+            offsets.push((spec_index, offset));
+        }
+        Ok(offsets)
+    }
+
+    /// Read the resources at their offsets. Entries pointed by multiple
+    /// spec indices are only read once, and recorded as shared entries
+    ///
+    /// # Arguments:
+    /// * reader - the reader of the config chunk
+    /// * entries_start - the absolute position where the entries start
+    /// * chunk_end - the absolute position where the config chunk ends
+    /// * offsets - pairs of spec index and the entry offset relative to `entries_start`
+    /// * entry_count - the number of entries including the missing ones
+    fn parse_config_resources<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        entries_start: u64,
+        chunk_end: u64,
+        offsets: Vec<(usize, u64)>,
+        entry_count: usize,
+    ) -> crate::Result<Resources> {
+        let mut resources = Vec::<ResourceEntry>::with_capacity(offsets.len());
+        let mut shared_entries = BTreeMap::new();
+        let mut read_offsets = HashMap::<u64, (usize, usize)>::with_capacity(offsets.len());
+        for (spec_index, offset) in offsets {
+            let mut resource = match read_offsets.entry(offset) {
+                Entry::Occupied(read) => {
+                    let (first_spec_index, resource_index) = *read.get();
+                    shared_entries.insert(spec_index, first_spec_index);
+                    resources[resource_index].clone()
+                }
+                Entry::Vacant(vacant) => {
+                    if entries_start + offset >= chunk_end {
+                        return Err(Error::bad_header(
+                            format!("offset {offset} of entry {spec_index} exceeds the chunk"),
+                            entries_start,
+                        ));
+                    }
+                    vacant.insert((spec_index, resources.len()));
+                    read_util::seek_to(reader, entries_start + offset)?;
+                    ResourceEntry::try_from(&mut *reader)?
+                }
+            };
             resource.spec_id = spec_index;
             resources.push(resource);
         }
-        resources.shrink_to_fit();
-        let resource_count = resources.len();
         Ok(Resources {
+            missing_entries: entry_count - resources.len(),
             resources,
-            missing_entries: entry_count - resource_count,
+            shared_entries,
         })
     }
 }
 
 impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Package {
-    type Error = std::io::Error;
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let base = reader.stream_position()?;
         let package_header = Header::try_from(&mut *reader)?;
-        debug_assert_eq!(package_header.resource_type, ResourceType::TablePackage);
+        package_header.expect(ResourceType::TablePackage, base)?;
+        let package_end = base + package_header.size;
         let package_id = read_util::read_u32(reader)?;
         let package_name = Self::parse_package_name(reader)?;
 
@@ -273,31 +526,62 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Package {
         let last_public_key = read_util::read_u32(reader)?;
         let _type_id_offset = read_util::read_u32(reader)?;
 
-        let type_names = StringPool::try_from(&mut *reader)?;
+        let type_names = Header::check_within(reader, package_end)
+            .and_then(|_| StringPool::try_from(&mut *reader))
+            .map_err(|error| error.within(chunk_name(ResourceType::StringPool, 0)))?;
         let mut types = (1..=type_names.strings.len())
             .map(Type::with_id)
             .collect::<Vec<_>>();
-        let key_names = StringPool::try_from(&mut *reader)?;
-
-        while let Ok(header) = Header::try_from(&mut *reader) {
-            match header.resource_type {
-                ResourceType::TableTypeSpec => {
-                    let mut specs = Specs::try_from(&mut *reader)?;
-                    specs.header_size = header.header_size;
-                    debug_assert!(
-                        &types[specs.type_id - 1].specs.is_none(),
-                        "Target type already has specs"
-                    );
-                    types[specs.type_id - 1].specs.replace(specs);
-                }
-                ResourceType::TableType => {
-                    let mut config = Config::try_from(&mut *reader)?;
-                    config.header_size = header.header_size;
-                    types[config.type_id - 1].configs.push(config);
-                }
-                flag => unreachable!("Unexpected flag: {flag:?}"),
+        let key_names = Header::check_within(reader, package_end)
+            .and_then(|_| StringPool::try_from(&mut *reader))
+            .map_err(|error| error.within(chunk_name(ResourceType::StringPool, 1)))?;
+        let mut library = None;
+        let mut overlayables = Vec::new();
+        let mut staged_aliases = Vec::new();
+        let mut unknown_chunks = Vec::new();
+        let mut chunk_order = Vec::new();
+
+        let mut chunk_counts = HashMap::new();
+        while reader.stream_position()? < package_end {
+            let chunk_start = reader.stream_position()?;
+            let resource_type = ResourceType::try_from(read_util::peek_u16(reader)?);
+            if !matches!(
+                resource_type,
+                Ok(ResourceType::TableTypeSpec
+                    | ResourceType::TableType
+                    | ResourceType::TableLibrary
+                    | ResourceType::TableOverlayable
+                    | ResourceType::TableStagedAlias)
+            ) {
+                chunk_order.push(PackageChunk::Unknown(unknown_chunks.len()));
+                unknown_chunks.push(UnknownChunk::parse_within(
+                    reader,
+                    unknown_chunks.len(),
+                    package_end,
+                )?);
+                continue;
+            }
+            let header = Header::try_from(&mut *reader)?;
+            if chunk_start + header.size > package_end {
+                return Err(Error::bad_header("chunk exceeds the package", chunk_start));
             }
+            let chunk_count = chunk_counts.entry(header.resource_type).or_insert(0);
+            let chunk = chunk_name(header.resource_type, *chunk_count);
+            *chunk_count += 1;
+            let parsed = Self::parse_chunk(
+                reader,
+                chunk_start,
+                &header,
+                &mut types,
+                &mut library,
+                &mut overlayables,
+                &mut staged_aliases,
+            )
+            .map_err(|error| error.within(chunk))?;
+            chunk_order.push(parsed);
+            reader.seek(SeekFrom::Start(chunk_start + header.size))?;
         }
+        reader.seek(SeekFrom::Start(package_end))?;
         Ok(Package {
             id: package_id,
             name: package_name,
@@ -306,29 +590,322 @@ impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Package {
             types,
             key_names,
             last_public_key,
+            library,
+            overlayables,
+            staged_aliases,
+            unknown_chunks,
+            chunk_order,
+            name_index: Default::default(),
         })
     }
 }
 
 impl Package {
-    fn parse_package_name<R: Read + Seek>(reader: &mut R) -> std::io::Result<String> {
+    fn parse_package_name<R: Read + Seek>(reader: &mut BufReader<R>) -> crate::Result<String> {
         read_util::read_string_utf16::<128, R>(reader)
     }
+
+    /// Parse a chunk in the package whose header has been read
+    ///
+    /// # Returns:
+    /// the parsed chunk, to record the order of the chunks
+    #[allow(clippy::too_many_arguments)]
+    fn parse_chunk<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        chunk_start: u64,
+        header: &Header,
+        types: &mut [Type],
+        library: &mut Option<Library>,
+        overlayables: &mut Vec<Overlayable>,
+        staged_aliases: &mut Vec<Vec<StagedAlias>>,
+    ) -> crate::Result<PackageChunk> {
+        let type_index = |type_id: usize| {
+            type_id
+                .checked_sub(1)
+                .filter(|index| *index < types.len())
+                .ok_or_else(|| Error::bad_header(format!("invalid type id {type_id}"), chunk_start))
+        };
+        let chunk = match header.resource_type {
+            ResourceType::TableTypeSpec => {
+                let mut specs = Specs::try_from(&mut *reader)?;
+                specs.header_size = header.header_size;
+                let index = type_index(specs.type_id)?;
+                let r#type = &mut types[index];
+                if r#type.specs.is_some() {
+                    return Err(Error::bad_header("type already has specs", chunk_start));
+                }
+                r#type.specs.replace(specs);
+                PackageChunk::Specs(index)
+            }
+            ResourceType::TableType => {
+                reader.seek(SeekFrom::Start(chunk_start))?;
+                let mut config = Config::try_from(&mut *reader)?;
+                let index = type_index(config.type_id)?;
+                let r#type = &mut types[index];
+                if config.is_sparse() {
+                    let spec_count = r#type.specs.as_ref().map(|specs| specs.specs.len());
+                    let resources = &mut config.resources;
+                    // the spec ids are in ascending order, so only the last one needs a check
+                    if let (Some(spec_count), Some(last)) = (spec_count, resources.resources.last())
+                    {
+                        if last.spec_id >= spec_count {
+                            return Err(Error::bad_header(
+                                format!("sparse entry {} exceeds {spec_count} specs", last.spec_id),
+                                chunk_start,
+                            ));
+                        }
+                    }
+                    let spec_count = spec_count.unwrap_or_default();
+                    let entry_count = resources
+                        .resources
+                        .last()
+                        .map(|resource| spec_count.max(resource.spec_id + 1))
+                        .unwrap_or(spec_count);
+                    resources.missing_entries =
+                        entry_count.saturating_sub(resources.resources.len());
+                }
+                r#type.configs.push(config);
+                PackageChunk::Config {
+                    type_index: index,
+                    config_index: r#type.configs.len() - 1,
+                }
+            }
+            ResourceType::TableLibrary => {
+                if library.is_some() {
+                    return Err(Error::bad_header(
+                        "package already has a library",
+                        chunk_start,
+                    ));
+                }
+                library.replace(Library::try_from(&mut *reader)?);
+                PackageChunk::Library
+            }
+            ResourceType::TableOverlayable => {
+                reader.seek(SeekFrom::Start(chunk_start))?;
+                overlayables.push(Overlayable::try_from(&mut *reader)?);
+                PackageChunk::Overlayable(overlayables.len() - 1)
+            }
+            ResourceType::TableStagedAlias => {
+                let count = read_util::read_u32(reader)? as usize;
+                let aliases = (0..count)
+                    .map(|_| StagedAlias::try_from(&mut *reader))
+                    .collect::<Result<Vec<_>, _>>()?;
+                staged_aliases.push(aliases);
+                PackageChunk::StagedAliases(staged_aliases.len() - 1)
+            }
+            resource_type => {
+                return Err(Error::bad_header(
+                    format!("unexpected {resource_type:?} chunk in package"),
+                    chunk_start,
+                ))
+            }
+        };
+        Ok(chunk)
+    }
 }
 
-impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Arsc {
-    type Error = std::io::Error;
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Library {
+    type Error = Error;
 
     fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
-        let _header = Header::try_from(&mut *reader)?;
-        let package_count = read_util::read_u32(reader)? as usize;
-        let global_string_pool = StringPool::try_from(&mut *reader)?;
-        let packages = std::iter::repeat_with(|| Package::try_from(&mut *reader))
-            .take(package_count)
+        let count = read_util::read_u32(reader)? as usize;
+        let entries = std::iter::repeat_with(|| LibraryEntry::try_from(&mut *reader))
+            .take(count)
             .collect::<Result<Vec<_>, Self::Error>>()?;
+        Ok(Library { entries })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for LibraryEntry {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let package_id = read_util::read_u32(reader)?;
+        let package_name = Package::parse_package_name(reader)?;
+        Ok(LibraryEntry {
+            package_id,
+            package_name,
+        })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Overlayable {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let base = reader.stream_position()?;
+        let header = Header::try_from(&mut *reader)?;
+        header.expect(ResourceType::TableOverlayable, base)?;
+        let name = read_util::read_string_utf16::<256, _>(reader)?;
+        let actor = read_util::read_string_utf16::<256, _>(reader)?;
+        reader.seek(SeekFrom::Start(base + header.header_size as u64))?;
+
+        let end = base + header.size;
+        let mut policies = Vec::new();
+        while reader.stream_position()? < end {
+            let chunk_start = reader.stream_position()?;
+            let chunk = chunk_name(ResourceType::TableOverlayablePolicy, policies.len());
+            let policy_header = Header::try_from(&mut *reader)
+                .and_then(|policy_header| {
+                    policy_header.expect(ResourceType::TableOverlayablePolicy, chunk_start)?;
+                    if chunk_start + policy_header.size > end {
+                        return Err(Error::bad_header(
+                            "chunk exceeds the overlayable",
+                            chunk_start,
+                        ));
+                    }
+                    Ok(policy_header)
+                })
+                .map_err(|error| error.within(chunk.clone()))?;
+            let policy =
+                OverlayablePolicy::try_from(&mut *reader).map_err(|error| error.within(chunk))?;
+            policies.push(policy);
+            reader.seek(SeekFrom::Start(chunk_start + policy_header.size))?;
+        }
+        Ok(Overlayable {
+            name,
+            actor,
+            policies,
+        })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for OverlayablePolicy {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let flags = PolicyFlags(read_util::read_u32(reader)?);
+        let entry_count = read_util::read_u32(reader)? as usize;
+        let entries = std::iter::repeat_with(|| read_util::read_u32(reader).map(ResourceId))
+            .take(entry_count)
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(OverlayablePolicy { flags, entries })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for StagedAlias {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let staged_id = ResourceId(read_util::read_u32(reader)?);
+        let finalized_id = ResourceId(read_util::read_u32(reader)?);
+        Ok(StagedAlias {
+            staged_id,
+            finalized_id,
+        })
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for UnknownChunk {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        let base = reader.stream_position()?;
+        let resource_type = read_util::read_u16(reader)?;
+        let header_size = read_util::read_u16(reader)? as usize;
+        let size = read_util::read_u32(reader)? as usize;
+        if header_size < 8 || size < header_size {
+            return Err(Error::bad_header(
+                format!("invalid header size {header_size} or chunk size {size}"),
+                base,
+            ));
+        }
+        let header = read_util::read_vec(reader, header_size - 8)?;
+        let body = read_util::read_vec(reader, size - header_size)?;
+        Ok(UnknownChunk {
+            resource_type,
+            header,
+            body,
+        })
+    }
+}
+
+impl UnknownChunk {
+    /// Parse an unknown chunk that must end before `parent_end`
+    ///
+    /// # Arguments:
+    /// * reader - the reader positioned at the start of the chunk
+    /// * index - the index of the chunk among the unknown chunks of its parent
+    /// * parent_end - the absolute position where the parent chunk ends
+    fn parse_within<R: Read + Seek>(
+        reader: &mut BufReader<R>,
+        index: usize,
+        parent_end: u64,
+    ) -> crate::Result<Self> {
+        let chunk_start = reader.stream_position()?;
+        let chunk = UnknownChunk::try_from(&mut *reader).and_then(|chunk| {
+            if reader.stream_position()? > parent_end {
+                return Err(Error::bad_header("chunk exceeds its parent", chunk_start));
+            }
+            Ok(chunk)
+        });
+        chunk.map_err(|error| error.within(format!("UnknownChunk[{index}]")))
+    }
+}
+
+impl<R: Read + Seek> TryFrom<&mut BufReader<R>> for Arsc {
+    type Error = Error;
+
+    fn try_from(reader: &mut BufReader<R>) -> Result<Self, Self::Error> {
+        Self::parse_table(reader).map_err(|error| error.within("Table"))
+    }
+}
+
+impl Arsc {
+    fn parse_table<R: Read + Seek>(reader: &mut BufReader<R>) -> crate::Result<Self> {
+        let base = reader.stream_position()?;
+        let header = Header::try_from(&mut *reader)?;
+        header.expect(ResourceType::Table, base)?;
+        let table_end = base + header.size;
+        // every chunk is checked to end within its parent, so the counts in a chunk
+        // are bounded by the actual data before they are used to allocate
+        let header_end = reader.stream_position()?;
+        let data_end = reader.seek(SeekFrom::End(0))?;
+        if table_end > data_end {
+            return Err(Error::Truncated {
+                location: Location::at(data_end),
+            });
+        }
+        reader.seek(SeekFrom::Start(header_end))?;
+        let _package_count = read_util::read_u32(reader)?;
+        let mut global_string_pool = None;
+        let mut packages = Vec::new();
+        let mut unknown_chunks = Vec::new();
+        let mut chunk_order = Vec::new();
+        while reader.stream_position()? < table_end {
+            match ResourceType::try_from(read_util::peek_u16(reader)?) {
+                Ok(ResourceType::StringPool) if global_string_pool.is_none() => {
+                    let string_pool = Header::check_within(reader, table_end)
+                        .and_then(|_| StringPool::try_from(&mut *reader))
+                        .map_err(|error| error.within(chunk_name(ResourceType::StringPool, 0)))?;
+                    global_string_pool.replace(string_pool);
+                    chunk_order.push(TableChunk::GlobalStringPool);
+                }
+                Ok(ResourceType::TablePackage) => {
+                    let chunk = chunk_name(ResourceType::TablePackage, packages.len());
+                    chunk_order.push(TableChunk::Package(packages.len()));
+                    let package = Header::check_within(reader, table_end)
+                        .and_then(|_| Package::try_from(&mut *reader))
+                        .map_err(|error| error.within(chunk))?;
+                    packages.push(package);
+                }
+                _ => {
+                    chunk_order.push(TableChunk::Unknown(unknown_chunks.len()));
+                    unknown_chunks.push(UnknownChunk::parse_within(
+                        reader,
+                        unknown_chunks.len(),
+                        table_end,
+                    )?);
+                }
+            }
+        }
+        let global_string_pool = global_string_pool
+            .ok_or_else(|| Error::bad_header("table has no global string pool", base))?;
         Ok(Arsc {
             global_string_pool,
             packages,
+            unknown_chunks,
+            chunk_order,
         })
     }
 }
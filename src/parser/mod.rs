@@ -2,7 +2,8 @@ mod components_read;
 mod read_util;
 
 use crate::components::Arsc;
-use std::io::{BufReader, Read, Result, Seek};
+use crate::error::Result;
+use std::io::{BufReader, Read, Seek};
 
 pub fn parse<R: Read + Seek>(reader: R) -> Result<Arsc> {
     let mut reader = BufReader::new(reader);
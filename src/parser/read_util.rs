@@ -1,12 +1,13 @@
+use crate::error::{Error, Location, Result};
 use paste::paste;
-use std::io::{Read, Result, Seek, SeekFrom};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
 
 macro_rules! read_num {
     ($num_type: ty) => {
         paste! {
-        pub fn [<read_ $num_type>]<R: Read>(reader: &mut R) -> Result<$num_type> {
+        pub fn [<read_ $num_type>]<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<$num_type> {
             let mut bytes = [0_u8; std::mem::size_of::<$num_type>()];
-            reader.read_exact(&mut bytes)?;
+            read_bytes(reader, &mut bytes)?;
             Ok(<$num_type>::from_le_bytes(bytes))
         }
         }
@@ -17,16 +18,61 @@ read_num!(u8);
 read_num!(u16);
 read_num!(u32);
 
+/// fill `bytes` with the data from reader
+/// ## Error:
+/// `Error::Truncated` if the reader ends before `bytes` is filled
+pub fn read_bytes<R: Read + Seek>(reader: &mut BufReader<R>, bytes: &mut [u8]) -> Result<()> {
+    match reader.read_exact(bytes) {
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Err(Error::Truncated {
+            location: Location::at(reader.stream_position()?),
+        }),
+        result => Ok(result?),
+    }
+}
+
+/// read `len` bytes into a vector, which grows with the data actually read
+/// ## Error:
+/// `Error::Truncated` if the reader ends before `len` bytes are read
+pub fn read_vec<R: Read + Seek>(reader: &mut BufReader<R>, len: usize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(Error::Truncated {
+            location: Location::at(reader.stream_position()?),
+        });
+    }
+    Ok(bytes)
+}
+
+/// read the next u16 without advancing the reader
+pub fn peek_u16<R: Read + Seek>(reader: &mut BufReader<R>) -> Result<u16> {
+    let bits = read_u16(reader)?;
+    reader.seek_relative(-2)?;
+    Ok(bits)
+}
+
 /// read 0-terminated string as utf16 encoding
 /// ## Warning:
 /// This function always reads `SIZE * 2` bytes
-pub fn read_string_utf16<const SIZE: usize, R: Read + Seek>(reader: &mut R) -> Result<String> {
-    let end = reader.stream_position()? + SIZE as u64 * 2;
+pub fn read_string_utf16<const SIZE: usize, R: Read + Seek>(
+    reader: &mut BufReader<R>,
+) -> Result<String> {
+    let start = reader.stream_position()?;
+    let end = start + SIZE as u64 * 2;
     let bytes = std::iter::repeat_with(|| read_u16(reader))
         .take(SIZE)
         .take_while(|byte| byte.as_ref().ok() != Some(&0))
         .collect::<Result<Vec<_>>>()?;
-    let string = String::from_utf16(&bytes).expect("Not Uft-16");
+    let string = String::from_utf16(&bytes).map_err(|_| Error::InvalidStringEncoding {
+        location: Location::at(start),
+    })?;
     reader.seek(SeekFrom::Start(end))?;
     Ok(string)
 }
+
+/// seek to the absolute `position`, while keeping the buffered data
+/// if the position is within the buffer
+pub fn seek_to<R: Read + Seek>(reader: &mut BufReader<R>, position: u64) -> Result<()> {
+    let current = reader.stream_position()?;
+    Ok(reader.seek_relative(position as i64 - current as i64)?)
+}
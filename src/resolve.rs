@@ -0,0 +1,814 @@
+use crate::{
+    Arsc, Config, DeviceConfig, Package, ResTableConfig, ResourceEntry, ResourceId, ResourceValue,
+    Value, ValueData,
+};
+use std::fmt::{Display, Formatter};
+
+/// The package id of the Android framework
+const SYS_PACKAGE_ID: u8 = 0x01;
+/// The package id of the application
+const APP_PACKAGE_ID: u8 = 0x7f;
+
+/// ResolvedValue is the result of following a chain of references
+#[derive(Debug, Clone)]
+pub struct ResolvedValue<'a> {
+    /// the ids that were followed, in order
+    pub chain: Vec<ResourceId>,
+    /// the terminal value. It is still a reference if the chain ends at a bag, e.g. a style
+    pub value: Value,
+    /// the entry that holds the terminal value, or None if no reference was followed
+    pub entry: Option<&'a ResourceEntry>,
+}
+
+/// Errors that can happen when following a chain of references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// No config of the resource matches the device, or the resource does not exist
+    NotFound(ResourceId),
+    /// The references form a cycle. The chain ends with the id that is seen twice
+    Cycle(Vec<ResourceId>),
+    /// The package of a dynamic reference is not in the library table or in this arsc
+    UnresolvedDynamicReference(ResourceId),
+    /// The resource is expected to be a bag, e.g. a style, but it is a plain value
+    NotABag(ResourceId),
+    /// The attribute is not set in the theme
+    UndefinedAttribute(ResourceId),
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::NotFound(id) => write!(f, "resource {id} cannot be found"),
+            ResolveError::Cycle(chain) => {
+                let chain = chain.iter().map(ResourceId::to_string).collect::<Vec<_>>();
+                write!(f, "references form a cycle: {}", chain.join(" -> "))
+            }
+            ResolveError::UnresolvedDynamicReference(id) => {
+                write!(f, "package of dynamic reference {id} cannot be found")
+            }
+            ResolveError::NotABag(id) => write!(f, "resource {id} is not a bag"),
+            ResolveError::UndefinedAttribute(id) => {
+                write!(f, "attribute {id} is not set in the theme")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl Arsc {
+    /// Find the resource entry that Android would choose on a device
+    ///
+    /// # Arguments:
+    /// * res_id - the resource id
+    /// * device - the configuration of the device
+    /// # Returns:
+    /// the entry in the best matching config, or None if no config matches the device
+    pub fn resolve(&self, res_id: ResourceId, device: &DeviceConfig) -> Option<&ResourceEntry> {
+        self.resolve_config(res_id, device)
+            .and_then(|config| config.resources.get(res_id.entry() as usize))
+    }
+
+    /// Find the config that Android would choose for a resource on a device
+    ///
+    /// # Arguments:
+    /// * res_id - the resource id
+    /// * device - the configuration of the device
+    /// # Returns:
+    /// the best matching config that has the resource, or None if no config matches the device
+    pub fn resolve_config(&self, res_id: ResourceId, device: &DeviceConfig) -> Option<&Config> {
+        let spec_id = res_id.entry() as usize;
+        let (_, r#type) = self.find_type(res_id)?;
+        r#type
+            .configs
+            .iter()
+            .filter(|config| config.id.matches(device))
+            .filter(|config| config.resources.get(spec_id).is_some())
+            .fold(None, |best: Option<&Config>, config| match best {
+                Some(best) if !config.id.is_better_than(&best.id, device) => Some(best),
+                _ => Some(config),
+            })
+    }
+
+    /// Follow the references from a resource until a value that is not a reference
+    ///
+    /// # Arguments:
+    /// * res_id - the resource id
+    /// * device - the configuration of the device
+    /// # Returns:
+    /// the terminal value and the chain of ids that leads to it, starting with `res_id`
+    pub fn resolve_reference(
+        &self,
+        res_id: ResourceId,
+        device: &DeviceConfig,
+    ) -> Result<ResolvedValue<'_>, ResolveError> {
+        let (package, _) = self
+            .find_type(res_id)
+            .ok_or(ResolveError::NotFound(res_id))?;
+        self.resolve_value(&ValueData::Reference(res_id).into(), package, device)
+    }
+
+    /// Follow the references from a value until a value that is not a reference.
+    /// Dynamic references are translated to runtime ids through the library table
+    /// of the package holding them, like `DynamicRefTable` of Android
+    ///
+    /// # Arguments:
+    /// * value - the value to start from
+    /// * package - the package that the value belongs to
+    /// * device - the configuration of the device
+    /// # Returns:
+    /// the terminal value and the chain of ids that leads to it
+    pub fn resolve_value<'a>(
+        &'a self,
+        value: &Value,
+        package: &'a Package,
+        device: &DeviceConfig,
+    ) -> Result<ResolvedValue<'a>, ResolveError> {
+        let mut chain = Vec::new();
+        let mut value = value.clone();
+        let mut package = package;
+        let mut entry = None;
+        loop {
+            let id = match value.data() {
+                ValueData::Reference(id) => id,
+                ValueData::DynamicReference(id) => self
+                    .resolve_dynamic_id(package, id)
+                    .ok_or(ResolveError::UnresolvedDynamicReference(id))?,
+                _ => break,
+            };
+            // `@null`
+            if id.0 == 0 {
+                break;
+            }
+            let seen = chain.contains(&id);
+            chain.push(id);
+            if seen {
+                return Err(ResolveError::Cycle(chain));
+            }
+            let (next_package, _) = self.find_type(id).ok_or(ResolveError::NotFound(id))?;
+            let next_entry = self.resolve(id, device).ok_or(ResolveError::NotFound(id))?;
+            package = next_package;
+            entry = Some(next_entry);
+            match &next_entry.value {
+                ResourceValue::Plain(next_value) => value = next_value.clone(),
+                ResourceValue::Bag { .. } => break,
+            }
+        }
+        Ok(ResolvedValue {
+            chain,
+            value,
+            entry,
+        })
+    }
+
+    /// Translate an id assigned at build time to the id of the package in this arsc,
+    /// following `DynamicRefTable::lookupResourceId` of Android
+    ///
+    /// # Arguments:
+    /// * package - the package that the id is used in
+    /// * res_id - the id in a dynamic reference or attribute
+    /// # Returns:
+    /// the runtime id, or None if the package of the id cannot be found
+    pub fn resolve_dynamic_id(&self, package: &Package, res_id: ResourceId) -> Option<ResourceId> {
+        let runtime_package_id = match res_id.package() {
+            SYS_PACKAGE_ID | APP_PACKAGE_ID => return Some(res_id),
+            // the id refers to the package itself
+            0 => package.id,
+            build_package_id => {
+                let library_entry = package
+                    .library
+                    .as_ref()?
+                    .entries
+                    .iter()
+                    .find(|entry| entry.package_id == build_package_id as u32)?;
+                self.packages
+                    .iter()
+                    .find(|package| package.name == library_entry.package_name)?
+                    .id
+            }
+        };
+        Some(ResourceId(
+            (runtime_package_id & 0xFF) << 24 | (res_id.0 & 0x00FF_FFFF),
+        ))
+    }
+}
+
+impl ResTableConfig {
+    /// Check if resources of this config can be used on the device,
+    /// following `ResTable_config::match` of Android
+    ///
+    /// A locale without a script is compared by its likely script, e.g. `zh-rTW` is written
+    /// in `Hant` and does not match a `zh-rCN` device, which uses `Hans`.
+    /// If the script of either locale is unknown, the regions must match instead.
+    ///
+    /// Notice: the likely scripts and the parent locales are a subset of the CLDR data
+    /// that Android uses, covering the common languages and their regional exceptions.
+    /// A language outside of them, e.g. `chr` (Cherokee), is treated as having an unknown
+    /// script, so its regions must match, while Android would match it by its script.
+    /// Provide the script, e.g. `b+chr+Cher`, to avoid the difference
+    pub fn matches(&self, device: &DeviceConfig) -> bool {
+        if self.mcc != 0 && self.mcc != device.mcc {
+            return false;
+        }
+        if self.mnc != 0 && self.mnc != device.mnc {
+            return false;
+        }
+        if self.language[0] != 0 || self.country[0] != 0 {
+            if !languages_are_equivalent(self.language, device.language) {
+                return false;
+            }
+            match (self.computed_script(), device.computed_script()) {
+                (Some(script), Some(device_script)) => {
+                    if script != device_script {
+                        return false;
+                    }
+                }
+                _ => {
+                    if self.country[0] != 0 && self.country != device.country {
+                        return false;
+                    }
+                }
+            }
+        }
+        if self.grammatical_inflection != 0
+            && self.grammatical_inflection != device.grammatical_inflection
+        {
+            return false;
+        }
+
+        let masked_fields = [
+            (
+                self.screen_layout,
+                device.screen_layout,
+                Self::MASK_LAYOUTDIR,
+            ),
+            (
+                self.screen_layout,
+                device.screen_layout,
+                Self::MASK_SCREENLONG,
+            ),
+            (self.ui_mode, device.ui_mode, Self::MASK_UI_MODE_TYPE),
+            (self.ui_mode, device.ui_mode, Self::MASK_UI_MODE_NIGHT),
+            (
+                self.screen_layout2,
+                device.screen_layout2,
+                Self::MASK_SCREENROUND,
+            ),
+            (self.color_mode, device.color_mode, Self::MASK_HDR),
+            (
+                self.color_mode,
+                device.color_mode,
+                Self::MASK_WIDE_COLOR_GAMUT,
+            ),
+            (self.orientation, device.orientation, u8::MAX),
+            (self.touchscreen, device.touchscreen, u8::MAX),
+            (self.input_flags, device.input_flags, Self::MASK_NAVHIDDEN),
+            (self.keyboard, device.keyboard, u8::MAX),
+            (self.navigation, device.navigation, u8::MAX),
+        ];
+        if masked_fields.iter().any(|(value, device_value, mask)| {
+            value & mask != 0 && value & mask != device_value & mask
+        }) {
+            return false;
+        }
+        // any screen sizes larger than the device do not match
+        let screen_size = self.screen_layout & Self::MASK_SCREENSIZE;
+        if screen_size != 0 && screen_size > device.screen_layout & Self::MASK_SCREENSIZE {
+            return false;
+        }
+        let upper_bounds = [
+            (
+                self.smallest_screen_width_dp,
+                device.smallest_screen_width_dp,
+            ),
+            (self.screen_width_dp, device.screen_width_dp),
+            (self.screen_height_dp, device.screen_height_dp),
+            (self.screen_width, device.screen_width),
+            (self.screen_height, device.screen_height),
+            (self.sdk_version, device.sdk_version),
+        ];
+        if upper_bounds
+            .iter()
+            .any(|(value, device_value)| *value != 0 && value > device_value)
+        {
+            return false;
+        }
+        let keys_hidden = self.input_flags & Self::MASK_KEYSHIDDEN;
+        let device_keys_hidden = device.input_flags & Self::MASK_KEYSHIDDEN;
+        // a request for keys exposed also matches the more recent soft keyboard
+        if keys_hidden != 0
+            && keys_hidden != device_keys_hidden
+            && (keys_hidden != Self::KEYSHIDDEN_NO || device_keys_hidden != Self::KEYSHIDDEN_SOFT)
+        {
+            return false;
+        }
+        if self.minor_version != 0 && self.minor_version != device.minor_version {
+            return false;
+        }
+        true
+    }
+
+    /// Check if this config is a better match for the device than `other`,
+    /// following `ResTable_config::isBetterThan` of Android.
+    /// Both configs are expected to match the device
+    pub fn is_better_than(&self, other: &ResTableConfig, device: &DeviceConfig) -> bool {
+        if self.mcc != other.mcc && device.mcc != 0 {
+            return self.mcc != 0;
+        }
+        if self.mnc != other.mnc && device.mnc != 0 {
+            return self.mnc != 0;
+        }
+        if self.is_locale_better_than(other, device) {
+            return true;
+        } else if other.is_locale_better_than(self, device) {
+            return false;
+        }
+        if self.grammatical_inflection != other.grammatical_inflection
+            && device.grammatical_inflection != 0
+        {
+            return self.grammatical_inflection != 0;
+        }
+        if (self.screen_layout ^ other.screen_layout) & Self::MASK_LAYOUTDIR != 0
+            && device.screen_layout & Self::MASK_LAYOUTDIR != 0
+        {
+            return self.screen_layout & Self::MASK_LAYOUTDIR
+                > other.screen_layout & Self::MASK_LAYOUTDIR;
+        }
+        // larger configs are filtered out by `matches`, so the largest one is the closest
+        if self.smallest_screen_width_dp != other.smallest_screen_width_dp {
+            return self.smallest_screen_width_dp > other.smallest_screen_width_dp;
+        }
+        if let Some(better) = closer_dimensions(
+            (self.screen_width_dp, self.screen_height_dp),
+            (other.screen_width_dp, other.screen_height_dp),
+            (device.screen_width_dp, device.screen_height_dp),
+        ) {
+            return better;
+        }
+        if (self.screen_layout ^ other.screen_layout) & Self::MASK_SCREENSIZE != 0
+            && device.screen_layout & Self::MASK_SCREENSIZE != 0
+        {
+            let screen_size = self.screen_layout & Self::MASK_SCREENSIZE;
+            let other_screen_size = other.screen_layout & Self::MASK_SCREENSIZE;
+            // undefined is considered as normal, unless the device is smaller than normal
+            let fixed = |screen_size: u8| {
+                if screen_size == 0
+                    && device.screen_layout & Self::MASK_SCREENSIZE >= Self::SCREENSIZE_NORMAL
+                {
+                    Self::SCREENSIZE_NORMAL
+                } else {
+                    screen_size
+                }
+            };
+            if fixed(screen_size) == fixed(other_screen_size) {
+                return screen_size != 0;
+            }
+            return fixed(screen_size) > fixed(other_screen_size);
+        }
+        let masked_fields = [
+            (
+                self.screen_layout,
+                other.screen_layout,
+                device.screen_layout,
+                Self::MASK_SCREENLONG,
+            ),
+            (
+                self.screen_layout2,
+                other.screen_layout2,
+                device.screen_layout2,
+                Self::MASK_SCREENROUND,
+            ),
+            (
+                self.color_mode,
+                other.color_mode,
+                device.color_mode,
+                Self::MASK_WIDE_COLOR_GAMUT,
+            ),
+            (
+                self.color_mode,
+                other.color_mode,
+                device.color_mode,
+                Self::MASK_HDR,
+            ),
+            (
+                self.orientation,
+                other.orientation,
+                device.orientation,
+                u8::MAX,
+            ),
+            (
+                self.ui_mode,
+                other.ui_mode,
+                device.ui_mode,
+                Self::MASK_UI_MODE_TYPE,
+            ),
+            (
+                self.ui_mode,
+                other.ui_mode,
+                device.ui_mode,
+                Self::MASK_UI_MODE_NIGHT,
+            ),
+        ];
+        for (value, other_value, device_value, mask) in masked_fields {
+            if (value ^ other_value) & mask != 0 && device_value & mask != 0 {
+                return value & mask != 0;
+            }
+        }
+        if self.density != other.density {
+            return self.is_density_better_than(other, device);
+        }
+        if self.touchscreen != other.touchscreen && device.touchscreen != 0 {
+            return self.touchscreen != 0;
+        }
+
+        let keys_hidden = self.input_flags & Self::MASK_KEYSHIDDEN;
+        let other_keys_hidden = other.input_flags & Self::MASK_KEYSHIDDEN;
+        let device_keys_hidden = device.input_flags & Self::MASK_KEYSHIDDEN;
+        if keys_hidden != other_keys_hidden && device_keys_hidden != 0 {
+            if keys_hidden == 0 {
+                return false;
+            }
+            if other_keys_hidden == 0 {
+                return true;
+            }
+            // keys exposed matches soft keyboard as well, but an exact match is better
+            if device_keys_hidden == keys_hidden {
+                return true;
+            }
+            if device_keys_hidden == other_keys_hidden {
+                return false;
+            }
+        }
+        let nav_hidden = self.input_flags & Self::MASK_NAVHIDDEN;
+        let other_nav_hidden = other.input_flags & Self::MASK_NAVHIDDEN;
+        if nav_hidden != other_nav_hidden && device.input_flags & Self::MASK_NAVHIDDEN != 0 {
+            if nav_hidden == 0 {
+                return false;
+            }
+            if other_nav_hidden == 0 {
+                return true;
+            }
+        }
+        if self.keyboard != other.keyboard && device.keyboard != 0 {
+            return self.keyboard != 0;
+        }
+        if self.navigation != other.navigation && device.navigation != 0 {
+            return self.navigation != 0;
+        }
+
+        if let Some(better) = closer_dimensions(
+            (self.screen_width, self.screen_height),
+            (other.screen_width, other.screen_height),
+            (device.screen_width, device.screen_height),
+        ) {
+            return better;
+        }
+        if self.sdk_version != other.sdk_version && device.sdk_version != 0 {
+            return self.sdk_version > other.sdk_version;
+        }
+        if self.minor_version != other.minor_version && device.minor_version != 0 {
+            return self.minor_version != 0;
+        }
+        false
+    }
+
+    /// Pick the density that needs less scaling, preferring scaling down over scaling up
+    fn is_density_better_than(&self, other: &ResTableConfig, device: &DeviceConfig) -> bool {
+        // the system default density is used if none specified
+        let density = |density: u16| match density {
+            Self::DENSITY_DEFAULT => Self::DENSITY_MEDIUM,
+            density => density,
+        } as i64;
+        let (this_density, other_density) = (density(self.density), density(other.density));
+        // an any density resource is always preferred over scaling a density bucket
+        if this_density == Self::DENSITY_ANY as i64 {
+            return true;
+        } else if other_density == Self::DENSITY_ANY as i64 {
+            return false;
+        }
+        let requested = match device.density {
+            Self::DENSITY_ANY => Self::DENSITY_MEDIUM as i64,
+            requested => density(requested),
+        };
+        let (low, high, this_is_higher) = if other_density > this_density {
+            (this_density, other_density, false)
+        } else {
+            (other_density, this_density, true)
+        };
+        if requested >= high {
+            this_is_higher
+        } else if low >= requested {
+            !this_is_higher
+        } else if (2 * low - requested) * high > requested * requested {
+            // scaling down is considered 2 times better than scaling up
+            !this_is_higher
+        } else {
+            this_is_higher
+        }
+    }
+
+    /// Compare the locales, following `ResTable_config::isLocaleBetterThan` of Android
+    fn is_locale_better_than(&self, other: &ResTableConfig, device: &DeviceConfig) -> bool {
+        if device.language[0] == 0 && device.country[0] == 0 {
+            return false;
+        }
+        if self.language[0] == 0
+            && self.country[0] == 0
+            && other.language[0] == 0
+            && other.country[0] == 0
+        {
+            return false;
+        }
+        if !languages_are_equivalent(self.language, other.language) {
+            // no-language resources are traditionally where US English lives,
+            // so they are preferred over the descendants of International English
+            if device.language == *b"en" {
+                if device.country == *b"US" {
+                    return if self.language[0] != 0 {
+                        self.country[0] == 0 || self.country == *b"US"
+                    } else {
+                        !(other.country[0] == 0 || other.country == *b"US")
+                    };
+                } else if is_close_to_us_english(device.country) {
+                    return if self.language[0] != 0 {
+                        is_close_to_us_english(self.country)
+                    } else {
+                        !is_close_to_us_english(other.country)
+                    };
+                }
+            }
+            return self.language[0] != 0;
+        }
+
+        // the scripts either are the same or cannot be computed after `matches`,
+        // so only the regions and the variants are compared
+        let region_comparison = compare_regions(self.country, other.country, device);
+        if region_comparison != 0 {
+            return region_comparison > 0;
+        }
+        let variant_matches = self.locale_variant == device.locale_variant;
+        if variant_matches != (other.locale_variant == device.locale_variant) {
+            return variant_matches;
+        }
+        let numbering_system_matches =
+            self.locale_numbering_system == device.locale_numbering_system;
+        if numbering_system_matches
+            != (other.locale_numbering_system == device.locale_numbering_system)
+        {
+            return numbering_system_matches;
+        }
+        // identical languages are better than equivalent ones, like Tagalog and Filipino
+        self.language == device.language && other.language != device.language
+    }
+
+    /// The script of the locale, or its likely script if none is provided,
+    /// following `localeDataComputeScript` of Android
+    ///
+    /// # Returns:
+    /// the script, or None if there is no language or its likely script is unknown
+    fn computed_script(&self) -> Option<[u8; 4]> {
+        if self.locale_script[0] != 0 {
+            return Some(self.locale_script);
+        }
+        if self.language[0] == 0 {
+            return None;
+        }
+        let (language, region) = (self.language_code(), self.region_code());
+        let script = REGIONAL_SCRIPTS
+            .iter()
+            .find(|(script_language, script_region, _)| {
+                *script_language == language && *script_region == region
+            })
+            .map(|(_, _, script)| *script)
+            .or_else(|| {
+                LIKELY_SCRIPTS
+                    .iter()
+                    .find(|(_, languages)| languages.contains(&language.as_str()))
+                    .map(|(script, _)| *script)
+            })?;
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(script.as_bytes());
+        Some(bytes)
+    }
+}
+
+/// Compare the sums of the differences between the dimensions and the device dimensions.
+/// Configs with larger dimensions are filtered out by `matches`
+///
+/// # Returns:
+/// Some(true) if `this` is closer, Some(false) if `other` is closer,
+/// or None if both are equally close
+fn closer_dimensions(this: (u16, u16), other: (u16, u16), device: (u16, u16)) -> Option<bool> {
+    let delta = |(width, height): (u16, u16)| {
+        let mut delta = 0_i64;
+        if device.0 != 0 {
+            delta += device.0 as i64 - width as i64;
+        }
+        if device.1 != 0 {
+            delta += device.1 as i64 - height as i64;
+        }
+        delta
+    };
+    let (this_delta, other_delta) = (delta(this), delta(other));
+    if this_delta == other_delta {
+        None
+    } else {
+        Some(this_delta < other_delta)
+    }
+}
+
+/// `tl` (Tagalog) and `fil` (Filipino) are considered the same language
+fn languages_are_equivalent(language: [u8; 2], other: [u8; 2]) -> bool {
+    const TAGALOG: [u8; 2] = *b"tl";
+    // `fil` packed into 2 bytes
+    const FILIPINO: [u8; 2] = [0xAD, 0x05];
+    language == other
+        || (language == TAGALOG && other == FILIPINO)
+        || (language == FILIPINO && other == TAGALOG)
+}
+
+/// The likely scripts of languages, in the form of `(script, languages)`.
+/// It is a subset of the likely subtags of CLDR, which `LocaleDataTables.cpp` of Android
+/// is generated from. Languages that are not listed have an unknown script
+const LIKELY_SCRIPTS: &[(&str, &[&str])] = &[
+    (
+        "Latn",
+        &[
+            "af", "ast", "az", "bs", "ca", "cs", "cy", "da", "de", "en", "eo", "es", "et", "eu",
+            "fi", "fil", "fo", "fr", "fy", "ga", "gd", "gl", "ha", "hr", "hu", "id", "ig", "in",
+            "is", "it", "jv", "lb", "lt", "lv", "mg", "mi", "ms", "mt", "nb", "nl", "nn", "no",
+            "om", "pl", "pt", "qu", "rm", "ro", "rw", "sk", "sl", "so", "sq", "sv", "sw", "tk",
+            "tl", "tr", "uz", "vi", "wo", "xh", "yo", "zu",
+        ],
+    ),
+    (
+        "Cyrl",
+        &[
+            "ba", "be", "bg", "ce", "cv", "kk", "ky", "mk", "mn", "ru", "sah", "sr", "tg", "tt",
+            "uk",
+        ],
+    ),
+    ("Arab", &["ar", "ckb", "fa", "ps", "sd", "ug", "ur"]),
+    ("Armn", &["hy"]),
+    ("Beng", &["as", "bn"]),
+    ("Deva", &["hi", "mr", "ne", "sa"]),
+    ("Ethi", &["am", "ti"]),
+    ("Geor", &["ka"]),
+    ("Grek", &["el"]),
+    ("Gujr", &["gu"]),
+    ("Guru", &["pa"]),
+    ("Hans", &["zh"]),
+    ("Hebr", &["he", "iw", "ji", "yi"]),
+    ("Jpan", &["ja"]),
+    ("Khmr", &["km"]),
+    ("Knda", &["kn"]),
+    ("Kore", &["ko"]),
+    ("Laoo", &["lo"]),
+    ("Mlym", &["ml"]),
+    ("Mymr", &["my"]),
+    ("Orya", &["or"]),
+    ("Sinh", &["si"]),
+    ("Taml", &["ta"]),
+    ("Telu", &["te"]),
+    ("Thaa", &["dv"]),
+    ("Thai", &["th"]),
+    ("Tibt", &["bo", "dz"]),
+];
+
+/// The likely scripts of regional locales that differ from the script of their language,
+/// in the form of `(language, region, script)`
+const REGIONAL_SCRIPTS: &[(&str, &str, &str)] = &[
+    ("az", "IR", "Arab"),
+    ("mn", "CN", "Mong"),
+    ("ms", "CC", "Arab"),
+    ("pa", "PK", "Arab"),
+    ("sd", "IN", "Deva"),
+    ("sr", "ME", "Latn"),
+    ("uz", "AF", "Arab"),
+    ("zh", "HK", "Hant"),
+    ("zh", "MO", "Hant"),
+    ("zh", "TW", "Hant"),
+];
+
+/// The parents of regional locales that do not fall back to the language directly,
+/// in the form of `(language, parent region, regions)`.
+/// It is a subset of the parent locales of CLDR, covering the Latin script languages
+const PARENT_REGIONS: &[(&str, &str, &[&str])] = &[
+    (
+        "en",
+        "001",
+        &[
+            "150", "AG", "AI", "AU", "BB", "BM", "BS", "BW", "BZ", "CA", "CC", "CK", "CM", "CX",
+            "CY", "DG", "DM", "ER", "FJ", "FK", "FM", "GB", "GD", "GG", "GH", "GI", "GM", "GY",
+            "HK", "IE", "IL", "IM", "IN", "IO", "JE", "JM", "KE", "KI", "KN", "KY", "LC", "LR",
+            "LS", "MG", "MO", "MS", "MT", "MU", "MV", "MW", "MY", "NA", "NF", "NG", "NR", "NU",
+            "NZ", "PG", "PK", "PN", "PW", "RW", "SB", "SC", "SD", "SG", "SH", "SL", "SS", "SX",
+            "SZ", "TC", "TK", "TO", "TT", "TV", "TZ", "UG", "VC", "VG", "VU", "WS", "ZA", "ZM",
+            "ZW",
+        ],
+    ),
+    (
+        "en",
+        "150",
+        &["AT", "BE", "CH", "DE", "DK", "FI", "NL", "SE", "SI"],
+    ),
+    (
+        "es",
+        "419",
+        &[
+            "AR", "BO", "BR", "BZ", "CL", "CO", "CR", "CU", "DO", "EC", "GT", "HN", "MX", "NI",
+            "PA", "PE", "PR", "PY", "SV", "US", "UY", "VE",
+        ],
+    ),
+    (
+        "pt",
+        "PT",
+        &[
+            "AO", "CH", "CV", "FR", "GQ", "GW", "LU", "MO", "MZ", "ST", "TL",
+        ],
+    ),
+];
+
+/// Find the parent region of a regional locale, e.g. `001` for `en-GB`.
+/// Regions without a known parent fall back to the language itself, which has no region
+fn parent_region<'a>(language: &str, region: &'a str) -> Option<&'a str> {
+    if region.is_empty() {
+        return None;
+    }
+    Some(
+        PARENT_REGIONS
+            .iter()
+            .find(|(parent_language, _, regions)| {
+                *parent_language == language && regions.contains(&region)
+            })
+            .map(|(_, parent, _)| *parent)
+            .unwrap_or_default(),
+    )
+}
+
+/// Check if `region` is an ancestor of `requested` or `requested` itself
+///
+/// # Returns:
+/// the distance from `requested` to `region` in the parent chain
+fn region_distance(language: &str, region: &str, requested: &str) -> Option<usize> {
+    std::iter::successors(Some(requested), |current| parent_region(language, current))
+        .position(|ancestor| ancestor == region)
+}
+
+/// Compare two regions by how close they are to the requested locale in the parent tree,
+/// following `localeDataCompareRegions` of Android
+///
+/// # Returns:
+/// a positive number if `left` is closer, a negative number if `right` is closer, 0 otherwise
+fn compare_regions(left: [u8; 2], right: [u8; 2], device: &DeviceConfig) -> i32 {
+    if left == right {
+        return 0;
+    }
+    let language = device.language_code();
+    let requested = device.region_code();
+    let region_code = |region: [u8; 2]| {
+        ResTableConfig {
+            country: region,
+            ..Default::default()
+        }
+        .region_code()
+    };
+    let (left, right) = (region_code(left), region_code(right));
+    let ancestors = |region: &str| -> Vec<String> {
+        std::iter::successors(Some(region), |current| parent_region(&language, current))
+            .map(str::to_string)
+            .collect()
+    };
+    let requested_ancestors = ancestors(&requested);
+    // an ancestor of the request is closer than any other region
+    if let Some(ancestor) = requested_ancestors
+        .iter()
+        .find(|ancestor| **ancestor == left || **ancestor == right)
+    {
+        return if *ancestor == left { 1 } else { -1 };
+    }
+    // otherwise the distance goes through the lowest common ancestor with the request,
+    // which is at least the language itself
+    let distance = |region: &str| {
+        ancestors(region)
+            .iter()
+            .enumerate()
+            .find_map(|(depth, ancestor)| {
+                requested_ancestors
+                    .iter()
+                    .position(|requested_ancestor| requested_ancestor == ancestor)
+                    .map(|index| (depth + index) as i32)
+            })
+            .unwrap_or_default()
+    };
+    distance(&right) - distance(&left)
+}
+
+/// A region is close to US English if it is not a descendant of International English
+fn is_close_to_us_english(region: [u8; 2]) -> bool {
+    let config = ResTableConfig {
+        country: region,
+        ..Default::default()
+    };
+    region_distance("en", "001", &config.region_code()).is_none()
+}
@@ -0,0 +1,206 @@
+use crate::{
+    Arsc, DeviceConfig, ResolveError, ResolvedValue, ResourceId, ResourceName, ResourceValue,
+    Value, ValueData,
+};
+use std::collections::BTreeMap;
+
+/// StyleAttribute is the effective value of an attribute in a style
+#[derive(Debug, Clone)]
+pub struct StyleAttribute<'a> {
+    pub value: &'a Value,
+    /// the style in the inheritance chain that the value comes from
+    pub source: ResourceId,
+}
+
+/// ResolvedStyle is a style merged with all its ancestors
+#[derive(Debug, Clone)]
+pub struct ResolvedStyle<'a> {
+    /// the style and its ancestors, from the style itself to the root
+    pub chain: Vec<ResourceId>,
+    /// the parent of the last style in `chain` that cannot be found in this arsc,
+    /// e.g. a style of the Android framework
+    pub missing_parent: Option<ResourceId>,
+    /// the effective value of every attribute, keyed by the attribute id.
+    /// Values of a style override the values of its ancestors
+    pub attributes: BTreeMap<ResourceId, StyleAttribute<'a>>,
+}
+
+impl Arsc {
+    /// Merge a style with its ancestors, following the `parent` of the bags.
+    ///
+    /// A style without a parent inherits from the style named by the part of its name
+    /// before the last dot if there is one, e.g. `Theme.App.Dark` inherits from `Theme.App`
+    ///
+    /// # Arguments:
+    /// * res_id - the id of the style
+    /// * device - the configuration of the device, used to choose the bags
+    /// # Returns:
+    /// the merged style, or an error if the style is not a bag or the parents form a cycle
+    pub fn resolve_style(
+        &self,
+        res_id: ResourceId,
+        device: &DeviceConfig,
+    ) -> Result<ResolvedStyle<'_>, ResolveError> {
+        let mut style = ResolvedStyle {
+            chain: Vec::new(),
+            missing_parent: None,
+            attributes: BTreeMap::new(),
+        };
+        let mut next = Some(res_id);
+        while let Some(id) = next {
+            let seen = style.chain.contains(&id);
+            style.chain.push(id);
+            if seen {
+                return Err(ResolveError::Cycle(style.chain));
+            }
+            let (package, _) = self.find_type(id).ok_or(ResolveError::NotFound(id))?;
+            let (parent, values) = match &self
+                .resolve(id, device)
+                .ok_or(ResolveError::NotFound(id))?
+                .value
+            {
+                ResourceValue::Bag { parent, values } => (*parent, values),
+                ResourceValue::Plain(_) => return Err(ResolveError::NotABag(id)),
+            };
+            for (attribute, value) in values {
+                let attribute = self
+                    .resolve_dynamic_id(package, *attribute)
+                    .unwrap_or(*attribute);
+                style
+                    .attributes
+                    .entry(attribute)
+                    .or_insert(StyleAttribute { value, source: id });
+            }
+            next = if parent.0 != 0 {
+                Some(self.resolve_dynamic_id(package, parent).unwrap_or(parent))
+            } else {
+                self.implicit_parent(id)
+            };
+            if let Some(parent) = next.filter(|parent| self.get(*parent).is_none()) {
+                style.missing_parent = Some(parent);
+                next = None;
+            }
+        }
+        Ok(style)
+    }
+
+    /// Find the parent that a style inherits from by its dotted name
+    fn implicit_parent(&self, res_id: ResourceId) -> Option<ResourceId> {
+        let view = self.get(res_id)?;
+        let (parent_name, _) = view.key_name.rsplit_once('.')?;
+        view.package.id_of(&ResourceName {
+            package: None,
+            r#type: view.type_name.to_string(),
+            name: parent_name.to_string(),
+        })
+    }
+}
+
+/// Theme is a set of styles applied on top of each other, used to resolve `?attr` values
+#[derive(Debug, Clone)]
+pub struct Theme<'a> {
+    arsc: &'a Arsc,
+    device: DeviceConfig,
+    attributes: BTreeMap<ResourceId, StyleAttribute<'a>>,
+}
+
+impl<'a> Theme<'a> {
+    /// Create a theme from styles
+    ///
+    /// # Arguments:
+    /// * arsc - the arsc that has the styles
+    /// * styles - the ids of the styles, where the later ones override the former ones
+    /// * device - the configuration of the device
+    /// # Returns:
+    /// the theme, or an error if a style cannot be resolved
+    pub fn new(
+        arsc: &'a Arsc,
+        styles: &[ResourceId],
+        device: &DeviceConfig,
+    ) -> Result<Self, ResolveError> {
+        let mut theme = Theme {
+            arsc,
+            device: device.clone(),
+            attributes: BTreeMap::new(),
+        };
+        for style in styles {
+            theme.apply_style(*style, true)?;
+        }
+        Ok(theme)
+    }
+
+    /// Apply a style and its ancestors to the theme, like `Theme::applyStyle` of Android
+    ///
+    /// # Arguments:
+    /// * style - the id of the style
+    /// * force - whether the style overrides the attributes that are already in the theme
+    pub fn apply_style(&mut self, style: ResourceId, force: bool) -> Result<(), ResolveError> {
+        let style = self.arsc.resolve_style(style, &self.device)?;
+        for (id, attribute) in style.attributes {
+            if force || !self.attributes.contains_key(&id) {
+                self.attributes.insert(id, attribute);
+            }
+        }
+        Ok(())
+    }
+
+    /// return the values of the attributes in the theme, keyed by the attribute id
+    pub fn attributes(&self) -> &BTreeMap<ResourceId, StyleAttribute<'a>> {
+        &self.attributes
+    }
+
+    /// Find the value of an attribute in the theme, and keep resolving it while
+    /// it is a reference or another attribute
+    ///
+    /// # Argument:
+    /// * attribute - the id of the attribute, e.g. the data of a `?attr/colorPrimary` value
+    /// # Returns:
+    /// the terminal value, with the attributes and the references that are followed in the chain
+    pub fn resolve_attribute(
+        &self,
+        attribute: ResourceId,
+    ) -> Result<ResolvedValue<'a>, ResolveError> {
+        let mut chain = Vec::new();
+        let mut entry = None;
+        let mut attribute = attribute;
+        loop {
+            let seen = chain.contains(&attribute);
+            chain.push(attribute);
+            if seen {
+                return Err(ResolveError::Cycle(chain));
+            }
+            let theme_value = self
+                .attributes
+                .get(&attribute)
+                .ok_or(ResolveError::UndefinedAttribute(attribute))?;
+            let (package, _) = self
+                .arsc
+                .find_type(theme_value.source)
+                .ok_or(ResolveError::NotFound(theme_value.source))?;
+            let resolved = self
+                .arsc
+                .resolve_value(theme_value.value, package, &self.device)?;
+            // the package that the terminal value belongs to
+            let package = match resolved.chain.last() {
+                Some(id) => self.arsc.find_type(*id).map_or(package, |(it, _)| it),
+                None => package,
+            };
+            chain.extend(resolved.chain);
+            entry = resolved.entry.or(entry);
+            attribute = match resolved.value.data() {
+                ValueData::Attribute(id) => id,
+                ValueData::DynamicAttribute(id) => self
+                    .arsc
+                    .resolve_dynamic_id(package, id)
+                    .ok_or(ResolveError::UnresolvedDynamicReference(id))?,
+                _ => {
+                    return Ok(ResolvedValue {
+                        chain,
+                        value: resolved.value,
+                        entry,
+                    })
+                }
+            };
+        }
+    }
+}
@@ -2,7 +2,11 @@ use crate::components::{
     Arsc, Config, Header, Package, ResourceEntry, ResourceValue, Spec, Specs, StringPool, Type,
     Value,
 };
-use crate::{Resources, Style, StyleSpan};
+use crate::writer::with_header::WithHeader;
+use crate::{
+    Library, LibraryEntry, Overlayable, OverlayablePolicy, ResTableConfig, Resources, StagedAlias,
+    Style, StyleSpan, UnknownChunk,
+};
 
 /// A trait for objects that have constant sizes
 /// when being written out in arsc format
@@ -25,7 +29,7 @@ impl<T: ConstByteSizing> ByteSizing for T {
 }
 
 impl ConstByteSizing for Value {
-    const SIZE: usize = 2 + 1 + 1 + 4; // size + zero + type + data_index
+    const SIZE: usize = 2 + 1 + 1 + 4; // size + zero + type + data
 }
 
 impl ByteSizing for ResourceValue {
@@ -42,22 +46,67 @@ impl ByteSizing for ResourceValue {
 
 impl ByteSizing for ResourceEntry {
     fn size(&self) -> usize {
+        if self.use_compact_form() {
+            return 2 + 2 + 4; // name_index + flags + data
+        }
         2 + 2 + 4 + self.value.size() // _size + name_index + flags + name_index + value. `spec_id` is not read in
     }
 }
 
 impl ByteSizing for Config {
     fn size(&self) -> usize {
-        Header::SIZE + 1 + 1 + 2 + 4 + 4 // type_id + res0 + res1 + entry_count + _entry_start
-            + self.id.len()// config_id
-            + padding(self.id.len())// config_id_padding
-            + self.resources.size()
+        self.get_header_size() as usize + self.entry_offsets_size() + self.resources.entries_size()
     }
 }
 
-impl ByteSizing for Resources {
-    fn size(&self) -> usize {
-        self.entry_count() * 4 + self.resources.iter().map(ByteSizing::size).sum::<usize>()
+impl Config {
+    /// The number of bytes required to write out the entry offsets, including the padding
+    pub(crate) fn entry_offsets_size(&self) -> usize {
+        let size = self.entry_offset_count() * self.entry_offset_size();
+        size + padding(size)
+    }
+}
+
+impl Resources {
+    /// The number of bytes required to write out all the resource entries
+    pub(crate) fn entries_size(&self) -> usize {
+        self.resources
+            .iter()
+            .zip(self.entry_offsets())
+            .filter(|(_, (_, written))| *written)
+            .map(|(resource, _)| resource.size())
+            .sum::<usize>()
+    }
+
+    /// Calculate the offset of each resource relative to the start of the entries.
+    /// A resource sharing the entry of an identical resource before it reuses
+    /// the offset of that resource instead of being written again
+    ///
+    /// # Returns:
+    /// the offset of each resource, and whether its entry needs to be written out
+    pub(crate) fn entry_offsets(&self) -> Vec<(usize, bool)> {
+        let mut offsets = Vec::<(usize, bool)>::with_capacity(self.resources.len());
+        let mut offset = 0;
+        for (index, resource) in self.resources.iter().enumerate() {
+            let shared_offset = self
+                .shared_entries
+                .get(&resource.spec_id)
+                .and_then(|owner| {
+                    self.resources[..index]
+                        .binary_search_by_key(owner, |resource| resource.spec_id)
+                        .ok()
+                })
+                .filter(|owner| self.resources[*owner].same_entry(resource))
+                .map(|owner| offsets[owner].0);
+            match shared_offset {
+                Some(shared_offset) => offsets.push((shared_offset, false)),
+                None => {
+                    offsets.push((offset, true));
+                    offset += resource.size();
+                }
+            }
+        }
+        offsets
     }
 }
 
@@ -143,14 +192,67 @@ impl ByteSizing for Package {
         + self.type_names.size()
         + self.types.iter().map(ByteSizing::size).sum::<usize>()
         + self.key_names.size()
+        + self.library.as_ref().map(ByteSizing::size).unwrap_or(0)
+        + self.overlayables.iter().map(ByteSizing::size).sum::<usize>()
+        + self.staged_aliases.iter().map(|aliases| aliases.size()).sum::<usize>()
+        + self.unknown_chunks.iter().map(ByteSizing::size).sum::<usize>()
+    }
+}
+
+impl ByteSizing for [StagedAlias] {
+    fn size(&self) -> usize {
+        Header::SIZE + 4 // header + count
+        + self.len() * StagedAlias::SIZE
+    }
+}
+
+impl ConstByteSizing for StagedAlias {
+    const SIZE: usize = 4 + 4; // staged_id + finalized_id
+}
+
+impl ByteSizing for Overlayable {
+    fn size(&self) -> usize {
+        Header::SIZE + 512 + 512 // header + name + actor
+        + self.policies.iter().map(ByteSizing::size).sum::<usize>()
+    }
+}
+
+impl ByteSizing for OverlayablePolicy {
+    fn size(&self) -> usize {
+        Header::SIZE + 4 + 4 // header + flags + entry_count
+        + self.entries.len() * 4
+    }
+}
+
+impl ByteSizing for Library {
+    fn size(&self) -> usize {
+        Header::SIZE + 4 // header + count
+        + self.entries.len() * LibraryEntry::SIZE
     }
 }
 
+impl ConstByteSizing for LibraryEntry {
+    const SIZE: usize = 4 + 256; // package_id + package_name
+}
+
 impl ByteSizing for Arsc {
     fn size(&self) -> usize {
         Header::SIZE + 4 // header + package_count
         + self.global_string_pool.size()
         + self.packages.iter().map(ByteSizing::size).sum::<usize>()
+        + self.unknown_chunks.iter().map(ByteSizing::size).sum::<usize>()
+    }
+}
+
+impl ByteSizing for ResTableConfig {
+    fn size(&self) -> usize {
+        self.encoded_size()
+    }
+}
+
+impl ByteSizing for UnknownChunk {
+    fn size(&self) -> usize {
+        Header::SIZE + self.header.len() + self.body.len()
     }
 }
 
@@ -1,12 +1,18 @@
 use super::write_util;
 use crate::components::{
-    Arsc, Config, Header, Package, ResourceEntry, ResourceValue, Spec, Specs, StringPool, Type,
-    Value,
+    Arsc, Config, Header, Package, ResourceEntry, ResourceType, ResourceValue, Spec, Specs,
+    StringPool, Value,
 };
+use crate::error::{chunk_name, Error, Result};
 use crate::writer::components_sizing::{padding, ByteSizing, ConstByteSizing};
 use crate::writer::with_header::WithHeader;
-use crate::{Resources, Style, StyleSpan};
-use std::io::{Result, Write};
+use crate::{
+    Library, LibraryEntry, Overlayable, OverlayablePolicy, PackageChunk, ResTableConfig, Resources,
+    StagedAlias, Style, StyleSpan, TableChunk, UnknownChunk,
+};
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::io::Write;
 
 /// types that implement this trait should define the function
 /// `write` to serialize and write the serialized bytes to the output
@@ -17,10 +23,82 @@ pub(crate) trait ArscSerializable {
     /// # Returns:
     /// the number of bytes that have been written out
     /// # Errors:
-    /// io errors
+    /// io errors, or overflow errors if a value cannot fit in its field
     fn write<W: Write>(&self, output: &mut W) -> Result<usize>;
 }
 
+/// Write a chunk, recording its name in the path of any error
+fn write_chunk<W: Write, C: ArscSerializable + ?Sized>(
+    output: &mut W,
+    chunk: &C,
+    resource_type: ResourceType,
+    index: usize,
+) -> Result<usize> {
+    chunk
+        .write(output)
+        .map_err(|error| error.within(chunk_name(resource_type, index)))
+}
+
+/// Write the unknown chunk at `unknown_chunks[index]` of its parent
+fn write_unknown_chunk<W: Write>(
+    output: &mut W,
+    unknown_chunks: &[UnknownChunk],
+    index: usize,
+) -> Result<usize> {
+    unknown_chunks[index]
+        .write(output)
+        .map_err(|error| error.within(format!("UnknownChunk[{index}]")))
+}
+
+impl ArscSerializable for UnknownChunk {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut position = write_util::write_u16(output, self.resource_type)?;
+        position += write_util::write_u16(output, Header::SIZE + self.header.len())?;
+        position += write_util::write_u32(output, self.size())?;
+        position += output.write(&self.header)?;
+        position += output.write(&self.body)?;
+        Ok(position)
+    }
+}
+
+impl ArscSerializable for ResTableConfig {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut buffer = Vec::with_capacity(ResTableConfig::SIZE);
+        write_util::write_u32(&mut buffer, self.size())?;
+        write_util::write_u16(&mut buffer, self.mcc)?;
+        write_util::write_u16(&mut buffer, self.mnc)?;
+        buffer.extend_from_slice(&self.language);
+        buffer.extend_from_slice(&self.country);
+        buffer.extend_from_slice(&[self.orientation, self.touchscreen]);
+        write_util::write_u16(&mut buffer, self.density)?;
+        buffer.extend_from_slice(&[
+            self.keyboard,
+            self.navigation,
+            self.input_flags,
+            self.grammatical_inflection,
+        ]);
+        write_util::write_u16(&mut buffer, self.screen_width)?;
+        write_util::write_u16(&mut buffer, self.screen_height)?;
+        write_util::write_u16(&mut buffer, self.sdk_version)?;
+        write_util::write_u16(&mut buffer, self.minor_version)?;
+        buffer.extend_from_slice(&[self.screen_layout, self.ui_mode]);
+        write_util::write_u16(&mut buffer, self.smallest_screen_width_dp)?;
+        write_util::write_u16(&mut buffer, self.screen_width_dp)?;
+        write_util::write_u16(&mut buffer, self.screen_height_dp)?;
+        buffer.extend_from_slice(&self.locale_script);
+        buffer.extend_from_slice(&self.locale_variant);
+        buffer.extend_from_slice(&[self.screen_layout2, self.color_mode]);
+        write_util::write_u16(&mut buffer, self.screen_config_pad2)?;
+        buffer.push(self.locale_script_was_computed);
+        buffer.extend_from_slice(&self.locale_numbering_system);
+        buffer.extend_from_slice(&self.end_padding);
+        // fields beyond `size` are not written, so shorter configs are kept as they were
+        buffer.truncate(self.known_size());
+        let position = output.write(&buffer)?;
+        Ok(position + output.write(&self.trailing)?)
+    }
+}
+
 impl ArscSerializable for Value {
     fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
         let mut written = write_util::write_u16(output, self.size)?;
@@ -36,10 +114,10 @@ impl ArscSerializable for ResourceValue {
         match self {
             ResourceValue::Plain(value) => value.write(output),
             ResourceValue::Bag { parent, values } => {
-                let mut written = write_util::write_u32(output, *parent)?;
+                let mut written = write_util::write_u32(output, parent.0)?;
                 written += write_util::write_u32(output, values.len())?;
                 for (index, value) in values {
-                    written += write_util::write_u32(output, *index)?;
+                    written += write_util::write_u32(output, index.0)?;
                     written += value.write(output)?;
                 }
                 Ok(written)
@@ -50,9 +128,17 @@ impl ArscSerializable for ResourceValue {
 
 impl ArscSerializable for ResourceEntry {
     fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        if let (true, ResourceValue::Plain(value)) = (self.use_compact_form(), &self.value) {
+            let flags = self.flags | (value.r#type as u16) << 8;
+            let mut written = write_util::write_u16(output, self.name_index)?;
+            written += write_util::write_u16(output, flags)?;
+            written += write_util::write_u32(output, value.data_index)?;
+            return Ok(written);
+        }
         let size = if self.is_bag() { 16 } else { 8 };
         let mut written = write_util::write_u16(output, size)?;
-        written += write_util::write_u16(output, self.flags)?;
+        let flags = self.flags & !ResourceEntry::ENTRY_FLAG_COMPACT;
+        written += write_util::write_u16(output, flags)?;
         written += write_util::write_u32(output, self.name_index)?;
         written += self.value.write(output)?;
         Ok(written)
@@ -233,79 +319,129 @@ impl ArscSerializable for Spec {
 
 impl ArscSerializable for Config {
     fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
-        let mut position = self.header().write(output)?;
+        let header = self.header();
+        let mut position = header.write(output)?;
         position += write_util::write_u8(output, self.type_id)?;
         position += write_util::write_u8(output, self.res0)?;
         position += write_util::write_u16(output, self.res1)?;
-        position += write_util::write_u32(output, self.resources.entry_count())?;
-        let entry_start = position
-            + 4
-            + self.id.len()
-            + padding(self.id.len())
-            + self.resources.entry_count() * 4;
+        position += write_util::write_u32(output, self.entry_offset_count())?;
+        let entry_start = header.header_size as usize + self.entry_offsets_size();
         position += write_util::write_u32(output, entry_start)?;
-        position += output.write(&self.id)?;
-        position += output.write(&vec![0; padding(self.id.len())])?;
-        position += self.resources.write(output)?;
+        position += self.id.write(output)?;
+        position += output.write(&vec![0; header.header_size as usize - position])?;
+        self.resources.check_order()?;
+        let offsets = self.resources.entry_offsets();
+        position += if self.is_sparse() {
+            self.resources.write_sparse_entries(output, &offsets)?
+        } else {
+            let written = self
+                .resources
+                .write_entries(output, &offsets, self.is_offset16())?;
+            written + output.write(&vec![0; padding(written)])?
+        };
+        for (resource, (_, written)) in self.resources.resources.iter().zip(offsets) {
+            if written {
+                position += resource.write(output)?;
+            }
+        }
 
         Ok(position)
     }
 }
 
-impl ArscSerializable for Resources {
-    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
-        let mut position = self.write_entries(output)?;
-        for resource in &self.resources {
-            position += resource.write(output)?;
+impl Resources {
+    /// Check the resources are sorted by `spec_id` without duplicates,
+    /// and within the entry count, which the entry offsets rely on
+    fn check_order(&self) -> Result<()> {
+        let unordered = self
+            .resources
+            .windows(2)
+            .find(|pair| pair[0].spec_id >= pair[1].spec_id);
+        if let Some(pair) = unordered {
+            return Err(Error::bad_header(
+                format!(
+                    "resource {} is not after resource {}",
+                    pair[1].spec_id, pair[0].spec_id
+                ),
+                0,
+            ));
+        }
+        match self.resources.last() {
+            Some(last) if last.spec_id >= self.entry_count() => Err(Error::bad_header(
+                format!(
+                    "resource {} exceeds {} entries",
+                    last.spec_id,
+                    self.entry_count()
+                ),
+                0,
+            )),
+            _ => Ok(()),
         }
-        Ok(position)
     }
-}
 
-impl Resources {
-    fn write_entries<W: Write>(&self, output: &mut W) -> Result<usize> {
-        let mut offset = 0;
+    fn write_sparse_entries<W: Write>(
+        &self,
+        output: &mut W,
+        offsets: &[(usize, bool)],
+    ) -> Result<usize> {
+        let mut written = 0;
+        for (resource, (offset, _)) in self.resources.iter().zip(offsets) {
+            debug_assert_eq!(offset % 4, 0, "entries are 4 bytes aligned");
+            written += write_util::write_u16(output, resource.spec_id)?;
+            written += write_util::write_u16(output, offset / 4)?;
+        }
+        Ok(written)
+    }
+
+    /// Write the dense entry offsets, where missing entries are written as `NO_ENTRY`
+    ///
+    /// # Arguments:
+    /// * output - a writer, where the bytes should be written to
+    /// * offsets - the offset of each resource, calculated by `entry_offsets`
+    /// * offset16 - true if each offset is written as a `u16` of `offset / 4`
+    fn write_entries<W: Write>(
+        &self,
+        output: &mut W,
+        offsets: &[(usize, bool)],
+        offset16: bool,
+    ) -> Result<usize> {
+        let write_offset = |output: &mut W, offset: Option<usize>| match offset {
+            // `u16::MAX` is reserved for `NO_ENTRY`, so it cannot be an offset
+            Some(offset) if offset16 && offset / 4 >= u16::MAX as usize => Err(Error::Overflow {
+                target: "u16",
+                location: Default::default(),
+            }),
+            Some(offset) if offset16 => {
+                debug_assert_eq!(offset % 4, 0, "entries are 4 bytes aligned");
+                write_util::write_u16(output, offset / 4)
+            }
+            None if offset16 => write_util::write_u16(output, u16::MAX),
+            Some(offset) => write_util::write_u32(output, offset),
+            None => write_util::write_u32(output, u32::MAX),
+        };
         let mut written = 0;
 
         let mut expected_spec_id = 0;
-        for resource in &self.resources {
+        for (resource, (offset, _)) in self.resources.iter().zip(offsets) {
             let gaps = resource.spec_id - expected_spec_id;
             for _ in 0..gaps {
-                written += write_util::write_u32(output, u32::MAX)?;
+                written += write_offset(output, None)?;
             }
-            written += write_util::write_u32(output, offset)?;
-            offset += resource.size();
+            written += write_offset(output, Some(*offset))?;
             expected_spec_id = resource.spec_id + 1;
         }
         for _ in expected_spec_id..self.entry_count() {
-            written += write_util::write_u32(output, u32::MAX)?;
+            written += write_offset(output, None)?;
         }
         Ok(written)
     }
 }
 
-impl ArscSerializable for Type {
-    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
-        let mut position = 0;
-        for specs in &self.specs {
-            position += specs.write(output)?;
-        }
-        for config in &self.configs {
-            position += config.write(output)?;
-        }
-        Ok(position)
-    }
-}
-
 impl ArscSerializable for Package {
     fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
         let mut position = self.header().write(output)?;
         position += write_util::write_u32(output, self.id)?;
-        let string_written = write_util::write_string_utf16(output, &self.name)?;
-        position += string_written;
-        if string_written < 256 {
-            position += output.write(&vec![0; 256 - string_written])?;
-        }
+        position += write_util::write_string_utf16_padded::<128, _>(output, &self.name)?;
 
         let type_string_offset = position + 5 * 4;
         position += write_util::write_u32(output, type_string_offset)?; // type_string_offset
@@ -315,23 +451,178 @@ impl ArscSerializable for Package {
         position += write_util::write_u32(output, self.last_public_key)?; // last_public_key
         position += write_util::write_u32(output, 0)?; // type_id_offset
 
-        position += self.type_names.write(output)?;
-        position += self.key_names.write(output)?;
-        for r#type in &self.types {
-            position += r#type.write(output)?;
+        position += write_chunk(output, &self.type_names, ResourceType::StringPool, 0)?;
+        position += write_chunk(output, &self.key_names, ResourceType::StringPool, 1)?;
+        let mut spec_count = 0;
+        let mut config_count = 0;
+        for chunk in self.ordered_chunks() {
+            position += match chunk {
+                PackageChunk::Specs(type_index) => {
+                    let specs = self.types[type_index].specs.as_ref();
+                    let specs = specs.expect("ordered chunks only list existing specs");
+                    spec_count += 1;
+                    write_chunk(output, specs, ResourceType::TableTypeSpec, spec_count - 1)?
+                }
+                PackageChunk::Config {
+                    type_index,
+                    config_index,
+                } => {
+                    let config = &self.types[type_index].configs[config_index];
+                    config_count += 1;
+                    write_chunk(output, config, ResourceType::TableType, config_count - 1)?
+                }
+                PackageChunk::Library => {
+                    let library = self.library.as_ref();
+                    let library = library.expect("ordered chunks only list an existing library");
+                    write_chunk(output, library, ResourceType::TableLibrary, 0)?
+                }
+                PackageChunk::Overlayable(index) => write_chunk(
+                    output,
+                    &self.overlayables[index],
+                    ResourceType::TableOverlayable,
+                    index,
+                )?,
+                PackageChunk::StagedAliases(index) => write_chunk(
+                    output,
+                    self.staged_aliases[index].as_slice(),
+                    ResourceType::TableStagedAlias,
+                    index,
+                )?,
+                PackageChunk::Unknown(index) => {
+                    write_unknown_chunk(output, &self.unknown_chunks, index)?
+                }
+            };
         }
         Ok(position)
     }
 }
 
+impl Package {
+    /// The chunks after the type names and key names in the order they are written.
+    /// Chunks in `chunk_order` come first, with the chunks that no longer exist skipped,
+    /// then the rest in the order of types, library, overlayables, staged aliases and unknown chunks
+    fn ordered_chunks(&self) -> Vec<PackageChunk> {
+        let mut chunks = Vec::new();
+        for (type_index, r#type) in self.types.iter().enumerate() {
+            if r#type.specs.is_some() {
+                chunks.push(PackageChunk::Specs(type_index));
+            }
+            chunks.extend(
+                (0..r#type.configs.len()).map(|config_index| PackageChunk::Config {
+                    type_index,
+                    config_index,
+                }),
+            );
+        }
+        if self.library.is_some() {
+            chunks.push(PackageChunk::Library);
+        }
+        chunks.extend((0..self.overlayables.len()).map(PackageChunk::Overlayable));
+        chunks.extend((0..self.staged_aliases.len()).map(PackageChunk::StagedAliases));
+        chunks.extend((0..self.unknown_chunks.len()).map(PackageChunk::Unknown));
+        in_order(&self.chunk_order, chunks)
+    }
+}
+
+impl ArscSerializable for [StagedAlias] {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut position = self.header().write(output)?;
+        position += write_util::write_u32(output, self.len())?;
+        for alias in self {
+            position += write_util::write_u32(output, alias.staged_id.0)?;
+            position += write_util::write_u32(output, alias.finalized_id.0)?;
+        }
+        Ok(position)
+    }
+}
+
+impl ArscSerializable for Overlayable {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut position = self.header().write(output)?;
+        position += write_util::write_string_utf16_padded::<256, _>(output, &self.name)?;
+        position += write_util::write_string_utf16_padded::<256, _>(output, &self.actor)?;
+        for (index, policy) in self.policies.iter().enumerate() {
+            position += write_chunk(output, policy, ResourceType::TableOverlayablePolicy, index)?;
+        }
+        Ok(position)
+    }
+}
+
+impl ArscSerializable for OverlayablePolicy {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut position = self.header().write(output)?;
+        position += write_util::write_u32(output, self.flags.0)?;
+        position += write_util::write_u32(output, self.entries.len())?;
+        for entry in &self.entries {
+            position += write_util::write_u32(output, entry.0)?;
+        }
+        Ok(position)
+    }
+}
+
+impl ArscSerializable for Library {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let mut position = self.header().write(output)?;
+        position += write_util::write_u32(output, self.entries.len())?;
+        for entry in &self.entries {
+            position += entry.write(output)?;
+        }
+        Ok(position)
+    }
+}
+
+impl ArscSerializable for LibraryEntry {
+    fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
+        let position = write_util::write_u32(output, self.package_id)?;
+        Ok(position + write_util::write_string_utf16_padded::<128, _>(output, &self.package_name)?)
+    }
+}
+
 impl ArscSerializable for Arsc {
     fn write<W: Write>(&self, output: &mut W) -> Result<usize> {
         let mut position = self.header().write(output)?;
         position += write_util::write_u32(output, self.packages.len())?;
-        position += self.global_string_pool.write(output)?;
-        for package in &self.packages {
-            position += package.write(output)?;
+        let mut chunks = vec![TableChunk::GlobalStringPool];
+        chunks.extend((0..self.packages.len()).map(TableChunk::Package));
+        chunks.extend((0..self.unknown_chunks.len()).map(TableChunk::Unknown));
+        for chunk in in_order(&self.chunk_order, chunks) {
+            position += match chunk {
+                TableChunk::GlobalStringPool => write_chunk(
+                    output,
+                    &self.global_string_pool,
+                    ResourceType::StringPool,
+                    0,
+                )?,
+                TableChunk::Package(index) => write_chunk(
+                    output,
+                    &self.packages[index],
+                    ResourceType::TablePackage,
+                    index,
+                )?,
+                TableChunk::Unknown(index) => {
+                    write_unknown_chunk(output, &self.unknown_chunks, index)?
+                }
+            };
         }
         Ok(position)
     }
 }
+
+/// Order the existing chunks of a parent as recorded in its `chunk_order`
+///
+/// # Arguments:
+/// * chunk_order - the recorded order, which may list chunks that no longer exist
+/// * existing - all the chunks of the parent in the default order
+/// # Returns:
+/// the existing chunks listed in `chunk_order`, followed by the rest of them in the default order
+fn in_order<C: Copy + Eq + Hash>(chunk_order: &[C], existing: Vec<C>) -> Vec<C> {
+    let exists = existing.iter().copied().collect::<HashSet<_>>();
+    let mut seen = HashSet::with_capacity(existing.len());
+    chunk_order
+        .iter()
+        .copied()
+        .filter(|chunk| exists.contains(chunk))
+        .chain(existing)
+        .filter(|chunk| seen.insert(*chunk))
+        .collect()
+}
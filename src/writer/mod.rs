@@ -1,6 +1,7 @@
 use crate::components::Arsc;
+use crate::error::Result;
 use components_writing::ArscSerializable;
-use std::io::{Result, Write};
+use std::io::Write;
 
 mod components_sizing;
 mod components_writing;
@@ -8,5 +9,31 @@ mod with_header;
 mod write_util;
 
 pub fn write<W: Write>(arsc: &Arsc, output: &mut W) -> Result<usize> {
-    arsc.write(output)
+    let mut output = CountingWriter {
+        inner: output,
+        count: 0,
+    };
+    arsc.write(&mut output).map_err(|error| {
+        let offset = output.count as u64;
+        error.within("Table").at_offset(offset)
+    })
+}
+
+/// A writer that counts the bytes written through it,
+/// so errors can be reported at the offset where they happened
+struct CountingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    count: usize,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
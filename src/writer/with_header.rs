@@ -1,5 +1,8 @@
-use super::components_sizing::ByteSizing;
-use crate::components::{Arsc, Config, Header, Package, ResourceType, Specs, StringPool};
+use super::components_sizing::{padding, ByteSizing, ConstByteSizing};
+use crate::components::{
+    Arsc, Config, Header, Library, Overlayable, OverlayablePolicy, Package, ResourceType, Specs,
+    StagedAlias, StringPool,
+};
 
 /// A trait for objects that are chunks (with header).
 /// It handles the header generation with predefined information
@@ -49,8 +52,39 @@ impl WithHeader for Specs {
 
 impl WithHeader for Config {
     fn get_header_size(&self) -> u16 {
-        self.header_size
+        // header + type_id + res0 + res1 + entry_count + entry_start + config_id + padding
+        let size = Header::SIZE + 1 + 1 + 2 + 4 + 4 + self.id.size() + padding(self.id.size());
+        // a longer header that has been read is kept
+        size.max(self.header_size as usize) as u16
     }
 
     const RESOURCE_TYPE: ResourceType = ResourceType::TableType;
 }
+
+impl WithHeader for Library {
+    fn get_header_size(&self) -> u16 {
+        0x000C
+    }
+    const RESOURCE_TYPE: ResourceType = ResourceType::TableLibrary;
+}
+
+impl WithHeader for Overlayable {
+    fn get_header_size(&self) -> u16 {
+        0x0408
+    }
+    const RESOURCE_TYPE: ResourceType = ResourceType::TableOverlayable;
+}
+
+impl WithHeader for OverlayablePolicy {
+    fn get_header_size(&self) -> u16 {
+        0x0010
+    }
+    const RESOURCE_TYPE: ResourceType = ResourceType::TableOverlayablePolicy;
+}
+
+impl WithHeader for [StagedAlias] {
+    fn get_header_size(&self) -> u16 {
+        0x000C
+    }
+    const RESOURCE_TYPE: ResourceType = ResourceType::TableStagedAlias;
+}
@@ -1,12 +1,16 @@
+use crate::error::{Error, Result};
 use paste::paste;
-use std::io::{Result, Write};
+use std::io::Write;
 
 macro_rules! write_num {
     ($num_type: ty) => {
         paste! {
         pub fn [<write_ $num_type>]<W: Write, I: TryInto<$num_type> + Copy>(writer: &mut W, data: I) -> Result<usize> {
-            let data = data.try_into().unwrap_or_else(|_| panic!(concat!("Cannot convert to", stringify!($num_type))));
-            writer.write(&data.to_le_bytes())
+            let data: $num_type = data.try_into().map_err(|_| Error::Overflow {
+                target: stringify!($num_type),
+                location: Default::default(),
+            })?;
+            Ok(writer.write(&data.to_le_bytes())?)
         }
         }
     };
@@ -23,3 +27,27 @@ pub fn write_string_utf16<W: Write>(writer: &mut W, string: &str) -> Result<usiz
     }
     Ok(written)
 }
+
+/// write a string as utf16 encoding, padded with 0 to `SIZE` characters
+/// ## Warning:
+/// This function always writes `SIZE * 2` bytes
+/// # Errors:
+/// overflow if the string does not leave room for the terminating 0 in `SIZE` characters
+pub fn write_string_utf16_padded<const SIZE: usize, W: Write>(
+    writer: &mut W,
+    string: &str,
+) -> Result<usize> {
+    if string.encode_utf16().count() >= SIZE {
+        return Err(Error::Overflow {
+            target: match SIZE {
+                128 => "[u16; 128]",
+                256 => "[u16; 256]",
+                _ => "[u16]",
+            },
+            location: Default::default(),
+        });
+    }
+    let mut written = write_string_utf16(writer, string)?;
+    written += writer.write(&vec![0; SIZE * 2 - written])?;
+    Ok(written)
+}
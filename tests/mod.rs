@@ -1,4 +1,10 @@
-use arsc::{parse, write_to};
+use arsc::{
+    complex_to_f32, f32_to_complex, parse, parse_from, write_to, ArscBuilder, AttrDefinition,
+    AttrFormat, DeviceConfig, Dimension, DimensionUnit, Error, Fraction, FractionUnit, Library,
+    LibraryEntry, PackageChunk, PolicyFlags, Quantity, ResTableConfig, ResolveError, ResourceEntry,
+    ResourceId, ResourceName, ResourceValue, StringPool, TableChunk, Theme, UnknownChunk, Value,
+    ValueData,
+};
 use std::io::Result;
 use std::path::Path;
 use walkdir::{DirEntry, WalkDir};
@@ -42,3 +48,1459 @@ fn read_then_write_to_bytes(path: &Path) -> Result<Vec<u8>> {
     write_to(&arsc, &mut output)?;
     Ok(output)
 }
+
+/// return the start of every chunk in the chunk at `start`
+fn child_chunks(bytes: &[u8], start: usize) -> Vec<usize> {
+    let u16_at = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let end = start + u32_at(start + 4) as usize;
+    let mut chunks = vec![];
+    let mut chunk = start + u16_at(start + 2);
+    while chunk < end {
+        chunks.push(chunk);
+        chunk += u32_at(chunk + 4) as usize;
+    }
+    chunks
+}
+
+#[test]
+fn test_multiple_packages() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("multi_package.arsc");
+    let arsc = parse(&path)?;
+    let package_ids = arsc
+        .packages
+        .iter()
+        .map(|package| package.id)
+        .collect::<Vec<_>>();
+    assert_eq!(package_ids, vec![0x7f, 0x80]);
+    assert_eq!(arsc.packages[1].name, "com.example.feature");
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+    Ok(())
+}
+
+#[test]
+fn test_library_chunk() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("shared_library.arsc");
+    let arsc = parse(&path)?;
+    let library = arsc.packages[0].library.as_ref().expect("library chunk");
+    let entries = library
+        .entries
+        .iter()
+        .map(|entry| (entry.package_id, entry.package_name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![(0x7f, "com.example.shared"), (0x02, "com.example.other")]
+    );
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    // aapt writes the library chunk before the types
+    let path = Path::new(SAMPLE_PATH).join("library_first.arsc");
+    let arsc = parse(&path)?;
+    assert_eq!(arsc.packages[0].chunk_order[0], PackageChunk::Library);
+    assert_eq!(arsc.packages[0].chunk_order[1], PackageChunk::Specs(0));
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+    Ok(())
+}
+
+#[test]
+fn test_overlayable_chunks() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("overlayable.arsc");
+    let arsc = parse(&path)?;
+    let overlayables = &arsc.packages[0].overlayables;
+    assert_eq!(overlayables.len(), 2);
+    assert_eq!(overlayables[0].name, "ThemeResources");
+    assert_eq!(overlayables[0].actor, "overlay://theme");
+    let policy = &overlayables[0].policies[1];
+    assert!(policy
+        .flags
+        .contains(PolicyFlags::SYSTEM_PARTITION | PolicyFlags::SIGNATURE));
+    assert!(!policy.flags.contains(PolicyFlags::PUBLIC));
+    assert_eq!(policy.entries, vec![ResourceId(0x7f020000)]);
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    let path = Path::new(SAMPLE_PATH).join("overlayable_reordered.arsc");
+    let arsc = parse(&path)?;
+    assert_eq!(
+        arsc.packages[0].chunk_order[2],
+        PackageChunk::Overlayable(0)
+    );
+    assert_eq!(
+        arsc.packages[0].chunk_order[5],
+        PackageChunk::Overlayable(1)
+    );
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    // the actor is a fixed field of 256 UTF-16 units, including the terminating 0
+    let mut arsc = parse(&path)?;
+    arsc.packages[0].overlayables[0].actor = "a".repeat(255);
+    write_to(&arsc, &mut vec![])?;
+    arsc.packages[0].overlayables[0].actor = "a".repeat(256);
+    let result = write_to(&arsc, &mut vec![]);
+    assert!(matches!(result, Err(Error::Overflow { .. })));
+    Ok(())
+}
+
+#[test]
+fn test_staged_aliases() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("staged_alias.arsc");
+    let arsc = parse(&path)?;
+    assert_eq!(arsc.packages[0].staged_aliases.len(), 1);
+    assert_eq!(arsc.packages[0].staged_aliases[0].len(), 2);
+    assert_eq!(
+        arsc.finalized_id(ResourceId(0x01ff0000)),
+        Some(ResourceId(0x0101065a))
+    );
+    assert_eq!(
+        arsc.packages[0].finalized_id(ResourceId(0x01fe0000)),
+        Some(ResourceId(0x01040030))
+    );
+    assert_eq!(arsc.finalized_id(ResourceId(0x01040030)), None);
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    let path = Path::new(SAMPLE_PATH).join("staged_alias_first.arsc");
+    let arsc = parse(&path)?;
+    assert_eq!(
+        arsc.packages[0].chunk_order[0],
+        PackageChunk::StagedAliases(0)
+    );
+    assert_eq!(
+        arsc.finalized_id(ResourceId(0x01ff0000)),
+        Some(ResourceId(0x0101065a))
+    );
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    // every staged alias chunk is kept at its own position
+    let path = Path::new(SAMPLE_PATH).join("two_staged_alias_chunks.arsc");
+    let arsc = parse(&path)?;
+    let package = &arsc.packages[0];
+    assert_eq!(package.staged_aliases.len(), 2);
+    assert!(package
+        .staged_aliases
+        .iter()
+        .all(|aliases| aliases.len() == 1));
+    assert_eq!(package.chunk_order[2], PackageChunk::StagedAliases(0));
+    assert_eq!(package.chunk_order[5], PackageChunk::StagedAliases(1));
+    assert_eq!(
+        arsc.finalized_id(ResourceId(0x01ff0000)),
+        Some(ResourceId(0x0101065a))
+    );
+    assert_eq!(
+        arsc.finalized_id(ResourceId(0x01fe0000)),
+        Some(ResourceId(0x01040030))
+    );
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+    Ok(())
+}
+
+#[test]
+fn test_sparse_configs() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("sparse.arsc");
+    let mut arsc = parse(&path)?;
+    let configs = &arsc.packages[0].types()[0].configs;
+    assert!(!configs[0].is_sparse());
+    assert!(configs[1].is_sparse());
+    let spec_ids = configs[1]
+        .resources
+        .resources
+        .iter()
+        .map(|resource| resource.spec_id)
+        .collect::<Vec<_>>();
+    assert_eq!(spec_ids, vec![3, 10]);
+    assert_eq!(configs[1].resources.missing_entries, 10);
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    for config in &mut arsc.packages[0].types_mut()[0].configs {
+        config.set_sparse(false);
+    }
+    let mut dense = vec![];
+    write_to(&arsc, &mut dense)?;
+    let mut arsc = parse_from(std::io::Cursor::new(&dense))?;
+    let configs = &mut arsc.packages[0].types_mut()[0].configs;
+    assert!(configs.iter().all(|config| !config.is_sparse()));
+    assert_eq!(configs[2].resources.missing_entries, 9);
+    let sparse_choices = configs
+        .iter_mut()
+        .map(|config| config.use_sparse_if_smaller())
+        .collect::<Vec<_>>();
+    assert_eq!(sparse_choices, vec![false, true, true]);
+
+    let mut sparse = vec![];
+    write_to(&arsc, &mut sparse)?;
+    assert!(sparse.len() < dense.len());
+    assert_eq!(std::fs::read(&path)?, sparse);
+    Ok(())
+}
+
+#[test]
+fn test_offset16_configs() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("offset16.arsc");
+    let mut arsc = parse(&path)?;
+    let configs = &arsc.packages[0].types()[0].configs;
+    assert!(configs.iter().all(|config| config.is_offset16()));
+    assert_eq!(configs[1].resources.resources.len(), 2);
+    assert_eq!(configs[1].resources.missing_entries, 1);
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    // 2 sparse offsets take 8 bytes, as many as 3 dense 16 bits offsets with padding
+    let config = &mut arsc.packages[0].types_mut()[0].configs[1];
+    assert!(!config.use_sparse_if_smaller());
+    config.set_offset16(false);
+    assert!(config.use_sparse_if_smaller());
+    config.set_sparse(false);
+    config.set_offset16(true);
+
+    for config in &mut arsc.packages[0].types_mut()[0].configs {
+        config.set_offset16(false);
+    }
+    let mut output = vec![];
+    write_to(&arsc, &mut output)?;
+    let arsc = parse_from(std::io::Cursor::new(output))?;
+    let configs = &arsc.packages[0].types()[0].configs;
+    assert!(!configs[1].is_offset16());
+    assert_eq!(configs[1].resources.resources[1].spec_id, 1);
+    assert_eq!(configs[1].resources.missing_entries, 1);
+
+    // an entry at 0x3FFFC would be encoded as `NO_ENTRY`
+    let mut arsc = parse(&path)?;
+    let config = &mut arsc.packages[0].types_mut()[0].configs[0];
+    let plain = config.resources.resources[0].clone();
+    let mut bag = plain.clone();
+    bag.flags = 0x0001;
+    bag.value = ResourceValue::Bag {
+        parent: ResourceId(0),
+        values: vec![(ResourceId(0x01010000), ValueData::IntDec(1).into())],
+    };
+    // a bag of 28 bytes, then entries of 16 bytes
+    let resources = std::iter::once(bag)
+        .chain(vec![plain; 16383])
+        .enumerate()
+        .map(|(spec_id, resource)| ResourceEntry {
+            spec_id,
+            ..resource
+        })
+        .collect();
+    config.resources.resources = resources;
+    config.resources.missing_entries = 0;
+    config.resources.shared_entries.clear();
+    match write_to(&arsc, &mut vec![]) {
+        Err(Error::Overflow { target, .. }) => assert_eq!(target, "u16"),
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    let config = &mut arsc.packages[0].types_mut()[0].configs[0];
+    config.resources.resources.pop();
+    assert!(write_to(&arsc, &mut vec![]).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_compact_entries() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("compact.arsc");
+    let mut arsc = parse(&path)?;
+    let integer = &arsc.packages[0].types()[1].configs[0].resources.resources[0];
+    assert!(integer.is_compact());
+    assert_eq!(integer.name_index, 2);
+    match &integer.value {
+        ResourceValue::Plain(value) => {
+            assert_eq!(value.r#type, Value::TYPE_INT_DEC);
+            assert_eq!(value.data(), ValueData::IntDec(42));
+        }
+        ResourceValue::Bag { .. } => panic!("integer is not a bag"),
+    }
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+
+    let compact_choices = arsc.packages[0]
+        .types_mut()
+        .iter_mut()
+        .flat_map(|r#type| r#type.configs.iter_mut())
+        .flat_map(|config| config.resources.resources.iter_mut())
+        .map(|resource| resource.set_compact(true))
+        .collect::<Vec<_>>();
+    assert_eq!(compact_choices, vec![true, true, true, true, true, false]);
+    let mut output = vec![];
+    write_to(&arsc, &mut output)?;
+    let arsc = parse_from(std::io::Cursor::new(output))?;
+    let color = &arsc.packages[0].types()[3].configs[0].resources.resources[0];
+    assert!(color.is_compact());
+    assert!(!arsc.packages[0].types()[4].configs[0].resources.resources[0].is_compact());
+    Ok(())
+}
+
+#[test]
+fn test_shared_entries() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("shared_entries.arsc");
+    let mut arsc = parse(&path)?;
+    let resources = &arsc.packages[0].types()[0].configs[0].resources;
+    let spec_ids = resources
+        .resources
+        .iter()
+        .map(|resource| resource.spec_id)
+        .collect::<Vec<_>>();
+    assert_eq!(spec_ids, vec![0, 1, 2, 3, 5]);
+    assert_eq!(resources.resources[0].value, resources.resources[2].value);
+    assert_eq!(resources.resources[1].value, resources.resources[3].value);
+    let shared_entries = resources
+        .shared_entries
+        .iter()
+        .map(|(spec_id, owner)| (*spec_id, *owner))
+        .collect::<Vec<_>>();
+    assert_eq!(shared_entries, vec![(2, 0), (3, 1)]);
+    let expected_bytes = std::fs::read(&path)?;
+    assert_eq!(expected_bytes, read_then_write_to_bytes(&path)?);
+
+    let resources = &mut arsc.packages[0].types_mut()[0].configs[0].resources;
+    if let ResourceValue::Plain(value) = &mut resources.resources[2].value {
+        value.data_index = 2;
+    }
+    let mut output = vec![];
+    write_to(&arsc, &mut output)?;
+    assert_eq!(output.len(), expected_bytes.len() + 16);
+    let arsc = parse_from(std::io::Cursor::new(output))?;
+    let resources = &arsc.packages[0].types()[0].configs[0].resources;
+    assert_ne!(resources.resources[0].value, resources.resources[2].value);
+    assert_eq!(resources.resources[2].value, resources.resources[4].value);
+    Ok(())
+}
+
+#[test]
+fn test_malformed_input() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("shared_entries.arsc");
+    let bytes = std::fs::read(&path)?;
+    let pool_size = u32::from_le_bytes(bytes[16..20].try_into().unwrap()) as usize;
+    let package_start = 12 + pool_size;
+
+    let truncated = parse_from(std::io::Cursor::new(&bytes[..bytes.len() / 2]));
+    assert!(matches!(truncated, Err(Error::Truncated { .. })));
+
+    let type_names_start = package_start + 0x0120;
+    let mut unknown_chunk = bytes.clone();
+    unknown_chunk[type_names_start..type_names_start + 2]
+        .copy_from_slice(&0x0299_u16.to_le_bytes());
+    match parse_from(std::io::Cursor::new(unknown_chunk)) {
+        Err(Error::UnknownChunkType {
+            resource_type,
+            location,
+        }) => {
+            assert_eq!(resource_type, 0x0299);
+            assert_eq!(location.offset, type_names_start as u64);
+            assert_eq!(
+                location.path,
+                vec!["Table", "TablePackage[0]", "StringPool[0]"]
+            );
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+
+    let mut bad_header = bytes.clone();
+    bad_header[2..4].copy_from_slice(&0_u16.to_le_bytes());
+    let result = parse_from(std::io::Cursor::new(bad_header));
+    assert!(matches!(result, Err(Error::BadHeader { .. })));
+
+    let mut arsc = parse(&path)?;
+    arsc.packages[0].types_mut()[0].configs[0].type_id = 0x100;
+    match write_to(&arsc, &mut vec![]) {
+        Err(Error::Overflow { target, location }) => {
+            assert_eq!(target, "u8");
+            assert_eq!(
+                location.path,
+                vec!["Table", "TablePackage[0]", "TableType[0]"]
+            );
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    arsc.packages[0].types_mut()[0].configs[0].type_id = 1;
+    arsc.packages[0].types_mut()[0].configs[0]
+        .resources
+        .resources
+        .swap(0, 1);
+    let result = write_to(&arsc, &mut vec![]);
+    assert!(matches!(result, Err(Error::BadHeader { .. })));
+
+    // names in fixed fields report where they overflow instead of corrupting the chunk
+    let library_path = Path::new(SAMPLE_PATH).join("shared_library.arsc");
+    let library_bytes = std::fs::read(&library_path)?;
+    let library_package = child_chunks(&library_bytes, 0)[1];
+    let library_chunk = child_chunks(&library_bytes, library_package)
+        .into_iter()
+        .find(|chunk| library_bytes[*chunk..*chunk + 2] == 0x0203_u16.to_le_bytes())
+        .unwrap();
+    let long_name = "a".repeat(128);
+    let mut library_arsc = parse(&library_path)?;
+    library_arsc.packages[0].name = long_name.clone();
+    match write_to(&library_arsc, &mut vec![]) {
+        Err(Error::Overflow { location, .. }) => {
+            assert_eq!(location.offset, library_package as u64 + 12);
+            assert_eq!(location.path, vec!["Table", "TablePackage[0]"]);
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    let mut library_arsc = parse(&library_path)?;
+    library_arsc.packages[0].library.as_mut().unwrap().entries[1].package_name = long_name;
+    match write_to(&library_arsc, &mut vec![]) {
+        Err(Error::Overflow { location, .. }) => {
+            assert_eq!(location.offset, library_chunk as u64 + 12 + 260 + 4);
+            assert_eq!(
+                location.path,
+                vec!["Table", "TablePackage[0]", "TableLibrary[0]"]
+            );
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+    arsc.packages[0].types_mut()[0].configs[0]
+        .resources
+        .resources
+        .swap(0, 1);
+    // resources beyond the entry count would write more offsets than the count
+    let config = &mut arsc.packages[0].types_mut()[0].configs[0];
+    let entry_count = config.resources.resources.len() + config.resources.missing_entries;
+    let last_spec_id = config.resources.resources.last().unwrap().spec_id;
+    let was_sparse = config.is_sparse();
+    config.resources.resources.last_mut().unwrap().spec_id = entry_count;
+    for sparse in [false, true] {
+        arsc.packages[0].types_mut()[0].configs[0].set_sparse(sparse);
+        let result = write_to(&arsc, &mut vec![]);
+        assert!(matches!(result, Err(Error::BadHeader { .. })));
+    }
+    let config = &mut arsc.packages[0].types_mut()[0].configs[0];
+    config.set_sparse(was_sparse);
+    config.resources.resources.last_mut().unwrap().spec_id = last_spec_id;
+    // entries beyond the 16 bits of an entry id cannot be addressed
+    arsc.packages[0].types_mut()[0].configs[0]
+        .resources
+        .missing_entries += 0x10000;
+    let mut too_many_entries = vec![];
+    write_to(&arsc, &mut too_many_entries)?;
+    let result = parse_from(std::io::Cursor::new(too_many_entries));
+    assert!(matches!(result, Err(Error::BadHeader { .. })));
+
+    // sizes are checked against the data before they are trusted
+    let mut huge_table = bytes[..40].to_vec();
+    huge_table[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    let result = parse_from(std::io::Cursor::new(huge_table));
+    assert!(matches!(result, Err(Error::Truncated { .. })));
+    let mut huge_pool = bytes.clone();
+    huge_pool[16..20].copy_from_slice(&0xFFFF_FFF0_u32.to_le_bytes());
+    let result = parse_from(std::io::Cursor::new(huge_pool));
+    assert!(matches!(result, Err(Error::BadHeader { .. })));
+
+    let bytes = std::fs::read(Path::new(SAMPLE_PATH).join("sparse.arsc"))?;
+    let package_start = child_chunks(&bytes, 0)[1];
+    let sparse_config = child_chunks(&bytes, package_start)[4];
+    let offsets_start = sparse_config
+        + u16::from_le_bytes([bytes[sparse_config + 2], bytes[sparse_config + 3]]) as usize;
+    let parse_with = |index: usize, value: u16| {
+        let mut bytes = bytes.clone();
+        bytes[offsets_start + index * 2..offsets_start + index * 2 + 2]
+            .copy_from_slice(&value.to_le_bytes());
+        parse_from(std::io::Cursor::new(bytes))
+    };
+    assert!(parse_with(0, 3).is_ok());
+    // the entries are [(3, offset), (10, offset)]
+    for (index, value) in [(0, 10), (2, 3), (2, 12), (1, 0xFFFF)] {
+        let result = parse_with(index, value);
+        match result {
+            Err(Error::BadHeader { location, .. }) => assert_eq!(
+                location.path,
+                vec!["Table", "TablePackage[0]", "TableType[1]"]
+            ),
+            result => panic!("Unexpected result: {result:?}"),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unknown_chunks() -> Result<()> {
+    let path = Path::new(SAMPLE_PATH).join("shared_entries.arsc");
+    let mut arsc = parse(&path)?;
+    let table_chunk = UnknownChunk {
+        resource_type: 0x0003,
+        header: vec![1, 2, 3, 4],
+        body: vec![5, 6, 7, 8, 9, 10, 11, 12],
+    };
+    let package_chunk = UnknownChunk {
+        resource_type: 0x02ff,
+        header: vec![],
+        body: vec![0xAB; 12],
+    };
+    arsc.unknown_chunks.push(table_chunk.clone());
+    arsc.packages[0].unknown_chunks.push(package_chunk.clone());
+    let mut output = vec![];
+    write_to(&arsc, &mut output)?;
+    assert_eq!(output.len(), std::fs::read(&path)?.len() + 8 + 12 + 8 + 12);
+
+    let parsed = parse_from(std::io::Cursor::new(&output))?;
+    assert_eq!(parsed.unknown_chunks, vec![table_chunk]);
+    assert_eq!(parsed.packages[0].unknown_chunks, vec![package_chunk]);
+    assert_eq!(
+        parsed.packages[0].types().len(),
+        arsc.packages[0].types().len()
+    );
+    let mut rewritten = vec![];
+    write_to(&parsed, &mut rewritten)?;
+    assert_eq!(output, rewritten);
+
+    // unknown chunks stay between the chunks they were found between
+    let path = Path::new(SAMPLE_PATH).join("unknown_between_types.arsc");
+    let arsc = parse(&path)?;
+    assert_eq!(
+        arsc.chunk_order,
+        vec![
+            TableChunk::GlobalStringPool,
+            TableChunk::Unknown(0),
+            TableChunk::Package(0)
+        ]
+    );
+    assert_eq!(arsc.unknown_chunks[0].resource_type, 0x0003);
+    let package = &arsc.packages[0];
+    assert_eq!(
+        package.chunk_order[..4],
+        [
+            PackageChunk::Specs(0),
+            PackageChunk::Config {
+                type_index: 0,
+                config_index: 0
+            },
+            PackageChunk::Unknown(0),
+            PackageChunk::Specs(1)
+        ]
+    );
+    assert_eq!(package.unknown_chunks[0].header, vec![1, 2, 3, 4]);
+    assert_eq!(std::fs::read(&path)?, read_then_write_to_bytes(&path)?);
+    Ok(())
+}
+
+#[test]
+fn test_typed_configs() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("1.arsc"))?;
+    let configs = arsc
+        .packages
+        .iter()
+        .flat_map(|package| package.types())
+        .flat_map(|r#type| &r#type.configs)
+        .map(|config| &config.id)
+        .collect::<Vec<_>>();
+    assert!(configs.iter().all(|config| config.size == 56));
+    assert!(configs
+        .iter()
+        .any(|config| config.language_code() == "ast" && config.region_code() == "ES"));
+    assert!(configs
+        .iter()
+        .any(|config| config.density == 480 && config.sdk_version == 4));
+
+    let path = Path::new(SAMPLE_PATH).join("basic.arsc");
+    let mut arsc = parse(&path)?;
+    assert!(arsc.packages[0]
+        .types()
+        .iter()
+        .flat_map(|r#type| &r#type.configs)
+        .any(|config| config.id.language_code() == "sr" && config.id.script() == "Latn"));
+    for size in [28, 32, 36, 48, 52, 56, 64, 68] {
+        let mut id = ResTableConfig {
+            size,
+            density: 480,
+            sdk_version: 21,
+            trailing: if size > 64 { vec![1, 2, 3, 4] } else { vec![] },
+            ..Default::default()
+        };
+        id.set_language_code("fil");
+        id.set_region_code("419");
+        assert_eq!(id.language_code(), "fil");
+        assert_eq!(id.region_code(), "419");
+        arsc.packages[0].types_mut()[0].configs[0].id = id.clone();
+        let mut output = vec![];
+        write_to(&arsc, &mut output)?;
+        let parsed = parse_from(std::io::Cursor::new(&output))?;
+        assert_eq!(parsed.packages[0].types()[0].configs[0].id, id);
+        let mut rewritten = vec![];
+        write_to(&parsed, &mut rewritten)?;
+        assert_eq!(output, rewritten);
+    }
+
+    // a header longer than the config is kept
+    let mut arsc = parse(&path)?;
+    let config = &mut arsc.packages[0].types_mut()[0].configs[0];
+    assert_eq!(config.header_size, 84);
+    config.header_size = 92;
+    let mut output = vec![];
+    write_to(&arsc, &mut output)?;
+    let parsed = parse_from(std::io::Cursor::new(&output))?;
+    let config = &parsed.packages[0].types()[0].configs[0];
+    assert_eq!(config.header_size, 92);
+    assert_eq!(
+        config.resources.resources.len(),
+        arsc.packages[0].types()[0].configs[0]
+            .resources
+            .resources
+            .len()
+    );
+    let mut rewritten = vec![];
+    write_to(&parsed, &mut rewritten)?;
+    assert_eq!(output, rewritten);
+    Ok(())
+}
+
+#[test]
+fn test_config_qualifiers() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let qualifiers = arsc.packages[0]
+        .types()
+        .iter()
+        .flat_map(|r#type| &r#type.configs)
+        .map(|config| config.id.to_string())
+        .collect::<Vec<_>>();
+    for expected in [
+        "",
+        "b+sr+Latn",
+        "fr-rCA",
+        "ldrtl-xxhdpi",
+        "w360dp-port",
+        "anydpi-v26",
+    ] {
+        assert!(qualifiers.iter().any(|qualifier| qualifier == expected));
+    }
+    for qualifier in &qualifiers {
+        let parsed = qualifier.parse::<ResTableConfig>().unwrap();
+        assert_eq!(&parsed.to_string(), qualifier);
+    }
+
+    let config = "en-rUS-land-xhdpi-v21".parse::<ResTableConfig>().unwrap();
+    assert_eq!(config.language_code(), "en");
+    assert_eq!(config.region_code(), "US");
+    assert_eq!(config.orientation, ResTableConfig::ORIENTATION_LAND);
+    assert_eq!(config.density, ResTableConfig::DENSITY_XHIGH);
+    assert_eq!(config.sdk_version, 21);
+
+    let config = "b+sr+Latn-night".parse::<ResTableConfig>().unwrap();
+    assert_eq!(config.script(), "Latn");
+    assert_eq!(
+        config.ui_mode & ResTableConfig::MASK_UI_MODE_NIGHT,
+        ResTableConfig::UI_MODE_NIGHT_YES
+    );
+    assert_eq!(config.sdk_version, 0);
+
+    for qualifier in [
+        "mcc310-mnc00-fr-rCA-night-xxhdpi-v26",
+        "masculine-ldrtl-sw600dp-w720dp-h1024dp-large-long-round-widecg-highdr-port-car-notnight",
+        "nodpi-finger-keyshidden-qwerty-navexposed-dpad-1920x1080-v33",
+        "b+es+419+u+nu+latn",
+        "fil-rPH-anydpi",
+    ] {
+        let config = qualifier.parse::<ResTableConfig>().unwrap();
+        assert_eq!(config.to_string(), qualifier);
+    }
+
+    let error = "v21-en".parse::<ResTableConfig>().unwrap_err();
+    assert_eq!(error.qualifier, "en");
+    assert!("en-xhdpi-land".parse::<ResTableConfig>().is_err());
+    assert!("unknown".parse::<ResTableConfig>().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_resolve_best_match() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let resolve = |res_id: u32, device: &str| {
+        let res_id = ResourceId(res_id);
+        let device = device.parse::<DeviceConfig>().unwrap();
+        let config = arsc.resolve_config(res_id, &device)?;
+        assert!(arsc.resolve(res_id, &device).is_some());
+        Some(config.id.to_string())
+    };
+
+    let string_id = 0x7f100000;
+    assert_eq!(resolve(string_id, "fr-rCA").as_deref(), Some("fr-rCA"));
+    assert_eq!(resolve(string_id, "fr-rFR").as_deref(), Some("fr"));
+    assert_eq!(
+        resolve(string_id, "b+sr+Latn").as_deref(),
+        Some("b+sr+Latn")
+    );
+    assert_eq!(resolve(string_id, "en-rUS").as_deref(), Some(""));
+    assert_eq!(resolve(string_id, "en-rGB").as_deref(), Some("en-rGB"));
+    assert_eq!(resolve(string_id, "es-rMX").as_deref(), Some("es"));
+    assert_eq!(resolve(string_id, "").as_deref(), Some(""));
+
+    let mipmap_id = 0x7f0d0000;
+    assert_eq!(
+        resolve(mipmap_id, "xxhdpi-v30").as_deref(),
+        Some("anydpi-v26")
+    );
+    assert_eq!(resolve(mipmap_id, "xxhdpi-v21").as_deref(), Some("xxhdpi"));
+    assert_eq!(resolve(mipmap_id, "400dpi-v21").as_deref(), Some("xxhdpi"));
+    assert_eq!(resolve(mipmap_id, "360dpi-v21").as_deref(), Some("xhdpi"));
+    assert_eq!(resolve(mipmap_id, "ldpi-v21").as_deref(), Some("mdpi"));
+
+    let style_type = &arsc.packages[0].types()[16];
+    let night_style = style_type
+        .configs
+        .iter()
+        .find(|config| config.id.to_string() == "night")
+        .and_then(|config| config.resources.resources.first())
+        .and_then(|resource| arsc.packages[0].resource_id(style_type, resource))
+        .map(|id| id.0)
+        .unwrap();
+    assert_eq!(night_style >> 16, 0x7f11);
+    assert_eq!(resolve(night_style, "night-v30").as_deref(), Some("night"));
+    assert_ne!(
+        resolve(night_style, "notnight-v30").as_deref(),
+        Some("night")
+    );
+    assert_eq!(resolve(0x7f200000, ""), None);
+    Ok(())
+}
+
+#[test]
+fn test_resolve_locale_scripts() {
+    let config = |qualifier: &str| qualifier.parse::<ResTableConfig>().unwrap();
+    let matches = |qualifier: &str, device: &str| config(qualifier).matches(&config(device));
+    let is_better = |qualifier: &str, other: &str, device: &str| {
+        config(qualifier).is_better_than(&config(other), &config(device))
+    };
+
+    // Traditional and Simplified Chinese do not match each other
+    assert!(!matches("zh-rCN", "zh-rTW"));
+    assert!(!matches("zh", "zh-rTW"));
+    assert!(matches("zh-rHK", "zh-rTW"));
+    assert!(!matches("zh-rTW", "zh-rCN"));
+    assert!(matches("zh", "zh-rCN"));
+    assert!(matches("zh-rSG", "zh-rCN"));
+    assert!(matches("b+zh+Hant", "zh-rTW"));
+    assert!(!matches("b+zh+Hant", "zh-rCN"));
+    // languages without a known script still need the same region
+    assert!(!matches("xx-rGB", "xx-rAU"));
+    // Cherokee is outside of the likely scripts, so unlike Android its regions must match,
+    // unless the script is provided
+    assert!(!matches("chr-rUS", "chr-rCA"));
+    assert!(matches("chr-rUS", "chr-rUS"));
+    assert!(matches("chr", "chr-rCA"));
+    assert!(matches("b+chr+Cher+US", "b+chr+Cher+CA"));
+
+    // en-rAU falls back to en-r001 and then to en
+    assert!(matches("en-rGB", "en-rAU"));
+    assert!(is_better("en-rAU", "en-rGB", "en-rAU"));
+    assert!(is_better("en-rGB", "en-rUS", "en-rAU"));
+    assert!(!is_better("en-rUS", "en-rGB", "en-rAU"));
+    assert!(is_better("en", "en-rGB", "en-rAU"));
+    assert!(is_better("en-r001", "en", "en-rAU"));
+    assert!(is_better("es-r419", "es-rES", "es-rAR"));
+    assert!(is_better("es-rPE", "es-rES", "es-rAR"));
+}
+
+#[test]
+fn test_value_data() {
+    let cases = [
+        (Value::TYPE_NULL, 0, ValueData::Null),
+        (Value::TYPE_NULL, 1, ValueData::Empty),
+        (
+            Value::TYPE_REFERENCE,
+            0x7f010000,
+            ValueData::Reference(ResourceId(0x7f010000)),
+        ),
+        (Value::TYPE_STRING, 5, ValueData::String(5)),
+        (Value::TYPE_FLOAT, 1.5f32.to_bits(), ValueData::Float(1.5)),
+        (Value::TYPE_INT_DEC, (-3i32) as u32, ValueData::IntDec(-3)),
+        (
+            Value::TYPE_INT_BOOLEAN,
+            0xFFFFFFFF,
+            ValueData::IntBoolean(true),
+        ),
+        (Value::TYPE_INT_BOOLEAN, 0, ValueData::IntBoolean(false)),
+        (
+            Value::TYPE_INT_COLOR_ARGB8,
+            0xFF00FF00,
+            ValueData::ColorArgb8(0xFF00FF00),
+        ),
+        (
+            0x42,
+            7,
+            ValueData::Unknown {
+                r#type: 0x42,
+                data: 7,
+            },
+        ),
+    ];
+    for (r#type, data, expected) in cases {
+        let value = Value {
+            size: 8,
+            zero: 0,
+            r#type,
+            data_index: data as usize,
+        };
+        assert_eq!(value.data(), expected);
+        assert_eq!(Value::from(expected), value);
+    }
+    assert!(Value::from(ValueData::String(0)).is_string());
+    assert!(!Value::from(ValueData::Dimension(0x1001)).is_string());
+    assert!(!Value::from(ValueData::IntHex(0x13)).is_string());
+}
+
+#[test]
+fn test_complex_values() {
+    // 16dp as encoded by aapt
+    let dimension = Dimension::from_complex(0x1001).unwrap();
+    assert_eq!(dimension, Dimension::new(16.0, DimensionUnit::Dp));
+    assert_eq!(dimension.to_string(), "16.0dp");
+    assert_eq!(dimension.to_complex(), 0x1001);
+
+    let fraction = Fraction::new(0.5, FractionUnit::Parent);
+    assert_eq!(fraction.to_complex(), 0x4000_0031);
+    assert_eq!(fraction.to_string(), "50%p");
+    assert_eq!(ValueData::from(fraction).fraction(), Some(fraction));
+    assert_eq!(ValueData::Fraction(0x4000_0031).dimension(), None);
+
+    for value in [0.0, 1.5, -2.25, 0.125, 300.75, 65535.5, 1e6, -0.001] {
+        let complex = f32_to_complex(value);
+        assert!((complex_to_f32(complex) - value).abs() <= value.abs() * 1e-5 + 1e-6);
+    }
+    // unknown units cannot be decoded
+    assert_eq!(Dimension::from_complex(0x100F), None);
+}
+
+#[test]
+fn test_resource_id() {
+    let id = ResourceId(0x7f010002);
+    assert_eq!(id.package(), 0x7f);
+    assert_eq!(id.type_id(), 0x01);
+    assert_eq!(id.entry(), 0x0002);
+    assert_eq!(id, ResourceId::new(0x7f, 0x01, 0x0002));
+    assert_eq!(ResourceId::try_from_parts(0x7f, 1, 2), Some(id));
+    // parts out of their ranges do not alias other resources
+    assert_eq!(ResourceId::try_from_parts(0x17f, 1, 2), None);
+    assert_eq!(ResourceId::try_from_parts(0x7f, 0x101, 2), None);
+    assert_eq!(ResourceId::try_from_parts(0x7f, 1, 0x10002), None);
+    assert_eq!(id.to_string(), "0x7f010002");
+    assert_eq!("0x7f010002".parse(), Ok(id));
+    assert_eq!("7f010002".parse(), Ok(id));
+    assert!("0xnope".parse::<ResourceId>().is_err());
+    assert_eq!(ResourceId(0x01010000).to_string(), "0x01010000");
+}
+
+#[test]
+fn test_lookup_by_id() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let view = arsc.get(ResourceId(0x7f100000)).unwrap();
+    assert_eq!(view.type_name, "string");
+    assert_eq!(view.key_name, "abc_action_bar_home_description");
+    // the string varies by locale
+    assert_eq!(view.spec_flags, Some(0x0004));
+    assert_eq!(view.entries.len(), 86);
+    assert!(view
+        .entries
+        .iter()
+        .any(|(config, _)| config.id.to_string() == "fr-rCA"));
+    assert!(view.entries.iter().all(|(_, entry)| entry.spec_id == 0));
+
+    let package = &arsc.packages[0];
+    assert_eq!(
+        package.get(view.id).map(|view| view.key_name),
+        Some(view.key_name)
+    );
+    assert!(arsc.get(ResourceId(0x7f10ffff)).is_none());
+    assert!(arsc.get(ResourceId(0x7f200000)).is_none());
+    assert!(arsc.get(ResourceId(0x01100000)).is_none());
+    Ok(())
+}
+
+#[test]
+fn test_lookup_by_name() -> Result<()> {
+    let mut arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let package_name = arsc.packages[0].name.clone();
+    let id = ResourceId(0x7f100000);
+    let name = format!("{package_name}:string/abc_action_bar_home_description");
+    assert_eq!(arsc.get_by_name(&name).map(|view| view.id), Some(id));
+    for name in [
+        "string/abc_action_bar_home_description",
+        "@string/abc_action_bar_home_description",
+        &format!("@{package_name}:string/abc_action_bar_home_description"),
+    ] {
+        assert_eq!(arsc.get_by_name(name).map(|view| view.id), Some(id));
+        assert_eq!(
+            arsc.packages[0].get_by_name(name).map(|view| view.id),
+            Some(id)
+        );
+    }
+    assert!(arsc
+        .get_by_name("android:string/abc_action_bar_home_description")
+        .is_none());
+    assert!(arsc.get_by_name("string/no_such_string").is_none());
+    assert!(arsc.get_by_name("not a name").is_none());
+
+    // the lookup follows the names after they change
+    let name_index = arsc.packages[0].types()[15].configs[0].resources.resources[0].name_index;
+    arsc.packages[0].key_names_mut().strings[name_index] = "renamed".to_string();
+    assert_eq!(
+        arsc.get_by_name("@string/renamed").map(|view| view.id),
+        Some(id)
+    );
+    assert!(arsc
+        .get_by_name("@string/abc_action_bar_home_description")
+        .is_none());
+    let type_name = &mut arsc.packages[0].type_names_mut().strings[15];
+    *type_name = "text".to_string();
+    assert_eq!(
+        arsc.get_by_name("@text/renamed").map(|view| view.id),
+        Some(id)
+    );
+    arsc.packages[0].types_mut()[15].configs.clear();
+    assert!(arsc.get_by_name("@text/renamed").is_none());
+    Ok(())
+}
+
+#[test]
+fn test_parse_resource_name() {
+    let name = |package: Option<&str>, r#type: &str, name: &str| ResourceName {
+        package: package.map(str::to_string),
+        r#type: r#type.to_string(),
+        name: name.to_string(),
+    };
+    let cases = [
+        ("string/app_name", name(None, "string", "app_name")),
+        ("@string/app_name", name(None, "string", "app_name")),
+        ("@+id/button", name(None, "id", "button")),
+        ("@android:string/ok", name(Some("android"), "string", "ok")),
+        ("@*android:string/ok", name(Some("android"), "string", "ok")),
+        (
+            "?android:textColor",
+            name(Some("android"), "attr", "textColor"),
+        ),
+        ("?attr/colorPrimary", name(None, "attr", "colorPrimary")),
+        (
+            "com.example:string/app_name",
+            name(Some("com.example"), "string", "app_name"),
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<ResourceName>().as_ref(), Ok(&expected));
+    }
+    assert_eq!(
+        "@android:string/ok"
+            .parse::<ResourceName>()
+            .unwrap()
+            .to_string(),
+        "android:string/ok"
+    );
+    for text in ["app_name", "@string/", ":string/a", "@string/a/b"] {
+        assert!(text.parse::<ResourceName>().is_err(), "{text}");
+    }
+}
+
+#[test]
+fn test_resolve_reference_chain() -> Result<()> {
+    let mut arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let device = DeviceConfig::default();
+    let resolved = arsc
+        .resolve_reference(ResourceId(0x7f050019), &device)
+        .unwrap();
+    assert_eq!(
+        resolved.chain,
+        vec![ResourceId(0x7f050019), ResourceId(0x7f050062)]
+    );
+    assert_eq!(resolved.value.data(), ValueData::ColorArgb8(0xff80cbc4));
+    assert!(resolved.entry.is_some());
+
+    // the chain ends at a framework color that is not in the table
+    assert_eq!(
+        arsc.resolve_reference(ResourceId(0x7f050024), &device)
+            .unwrap_err(),
+        ResolveError::NotFound(ResourceId(0x0106000c))
+    );
+
+    // values that are not references are terminal
+    let package = &arsc.packages[0];
+    let literal = Value::from(ValueData::IntDec(1));
+    let resolved = arsc.resolve_value(&literal, package, &device).unwrap();
+    assert!(resolved.chain.is_empty() && resolved.entry.is_none());
+    assert_eq!(resolved.value, literal);
+
+    let colors = &mut arsc.packages[0].types_mut()[4];
+    for config in &mut colors.configs {
+        for entry in &mut config.resources.resources {
+            if entry.spec_id == 0x62 {
+                entry.value =
+                    ResourceValue::Plain(ValueData::Reference(ResourceId(0x7f050019)).into());
+            }
+        }
+    }
+    assert_eq!(
+        arsc.resolve_reference(ResourceId(0x7f050019), &device)
+            .unwrap_err(),
+        ResolveError::Cycle(vec![
+            ResourceId(0x7f050019),
+            ResourceId(0x7f050062),
+            ResourceId(0x7f050019)
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_resolve_dynamic_reference() -> Result<()> {
+    let mut arsc = parse(Path::new(SAMPLE_PATH).join("multi_package.arsc"))?;
+    let device = DeviceConfig::default();
+    // the feature package refers to the base package with the build time id 0x02
+    arsc.packages[1].library = Some(Library {
+        entries: vec![LibraryEntry {
+            package_id: 0x02,
+            package_name: arsc.packages[0].name.clone(),
+        }],
+    });
+    let feature = &arsc.packages[1];
+    assert_eq!(
+        arsc.resolve_dynamic_id(feature, ResourceId(0x02020000)),
+        Some(ResourceId(0x7f020000))
+    );
+    assert_eq!(
+        arsc.resolve_dynamic_id(feature, ResourceId(0x00020000)),
+        Some(ResourceId(0x80020000))
+    );
+    assert_eq!(
+        arsc.resolve_dynamic_id(feature, ResourceId(0x05020000)),
+        None
+    );
+    let value = Value::from(ValueData::DynamicReference(ResourceId(0x02020000)));
+    let resolved = arsc.resolve_value(&value, feature, &device).unwrap();
+    assert_eq!(resolved.chain, vec![ResourceId(0x7f020000)]);
+
+    let arsc = parse(Path::new(SAMPLE_PATH).join("shared_library.arsc"))?;
+    let value = Value::from(ValueData::DynamicReference(ResourceId(0x00020001)));
+    assert_eq!(
+        arsc.resolve_value(&value, &arsc.packages[0], &device)
+            .unwrap_err(),
+        ResolveError::UnresolvedDynamicReference(ResourceId(0x02010000))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_resolve_style() -> Result<()> {
+    let mut arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let device = DeviceConfig::default();
+
+    // Animation.Design.BottomSheetDialog -> Animation.AppCompat.Dialog
+    // -> Base.Animation.AppCompat.Dialog -> android:Animation
+    let style = arsc.resolve_style(ResourceId(0x7f110006), &device).unwrap();
+    assert_eq!(
+        style.chain,
+        vec![
+            ResourceId(0x7f110006),
+            ResourceId(0x7f110003),
+            ResourceId(0x7f11000a)
+        ]
+    );
+    assert_eq!(style.missing_parent, Some(ResourceId(0x01030000)));
+    let sources = style
+        .attributes
+        .values()
+        .map(|attribute| attribute.source)
+        .collect::<Vec<_>>();
+    // both the child and the base set the window animations, and the child wins
+    assert_eq!(sources, vec![ResourceId(0x7f110006); 2]);
+
+    // Base.TextAppearance.AppCompat.SearchResult has no parent,
+    // so it inherits from Base.TextAppearance.AppCompat by its name
+    let style = arsc.resolve_style(ResourceId(0x7f110025), &device).unwrap();
+    assert_eq!(
+        style.chain,
+        vec![ResourceId(0x7f110025), ResourceId(0x7f110013)]
+    );
+    assert_eq!(style.missing_parent, Some(ResourceId(0x010301ed)));
+
+    assert_eq!(
+        arsc.resolve_style(ResourceId(0x7f050019), &device)
+            .unwrap_err(),
+        ResolveError::NotABag(ResourceId(0x7f050019))
+    );
+
+    for config in &mut arsc.packages[0].types_mut()[16].configs {
+        for entry in &mut config.resources.resources {
+            if let (0x0a, ResourceValue::Bag { parent, .. }) = (entry.spec_id, &mut entry.value) {
+                *parent = ResourceId(0x7f110006);
+            }
+        }
+    }
+    assert!(matches!(
+        arsc.resolve_style(ResourceId(0x7f110006), &device),
+        Err(ResolveError::Cycle(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_theme_attributes() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let device = DeviceConfig::default();
+    // Base.Theme.AppCompat
+    let theme = Theme::new(&arsc, &[ResourceId(0x7f110047)], &device).unwrap();
+    let resolved = theme.resolve_attribute(ResourceId(0x0101009b)).unwrap();
+    // ?android:attr -> ?android:attr -> ?attr/colorAccent -> @color -> @color -> #ff80cbc4
+    assert_eq!(
+        resolved.chain,
+        [0x0101009b, 0x01010435, 0x7f0300ae, 0x7f050019, 0x7f050062]
+            .into_iter()
+            .map(ResourceId)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(resolved.value.data(), ValueData::ColorArgb8(0xff80cbc4));
+    assert!(resolved.entry.is_some());
+    assert_eq!(
+        theme.resolve_attribute(ResourceId(0x7f0309ff)).unwrap_err(),
+        ResolveError::UndefinedAttribute(ResourceId(0x7f0309ff))
+    );
+
+    // an overlay without force keeps the attributes that are already set
+    let mut overlaid = theme.clone();
+    overlaid.apply_style(ResourceId(0x7f110048), false).unwrap();
+    assert!(overlaid.attributes().len() >= theme.attributes().len());
+    for (id, attribute) in theme.attributes() {
+        assert_eq!(overlaid.attributes()[id].source, attribute.source);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_attr_definition() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    // collapsedTitleGravity
+    let gravity = arsc.attr_definition(ResourceId(0x7f0300ab)).unwrap();
+    assert_eq!(gravity.format, AttrFormat::FLAGS);
+    assert_eq!(gravity.symbols.len(), 10);
+    assert_eq!(gravity.symbols[0].name.as_deref(), Some("top"));
+    assert_eq!(
+        gravity.format_value(0x0080_0013).as_deref(),
+        Some("center_vertical|start")
+    );
+    assert_eq!(gravity.format_value(0x11).as_deref(), Some("center"));
+    assert_eq!(gravity.format_value(0x0400), None);
+    assert_eq!(
+        gravity.parse_value("center_vertical|start"),
+        Some(0x0080_0013)
+    );
+    assert_eq!(gravity.parse_value("center_vertical|middle"), None);
+
+    // animationMode
+    let mode = arsc.attr_definition(ResourceId(0x7f03002f)).unwrap();
+    assert_eq!(mode.format, AttrFormat::ENUM);
+    assert_eq!(mode.format_value(1).as_deref(), Some("fade"));
+    assert_eq!(mode.parse_value("slide"), Some(0));
+    assert_eq!(mode.parse_value("slide|fade"), None);
+
+    // behavior_peekHeight accepts a dimension or `auto`
+    let peek_height = arsc.attr_definition(ResourceId(0x7f030058)).unwrap();
+    assert!(peek_height
+        .format
+        .contains(AttrFormat::DIMENSION | AttrFormat::ENUM));
+    assert_eq!(peek_height.format_value(u32::MAX).as_deref(), Some("auto"));
+
+    let bounded = AttrDefinition::from_bag(&[
+        (
+            AttrDefinition::ATTR_TYPE,
+            Value::from(ValueData::IntHex(AttrFormat::INTEGER.0)),
+        ),
+        (AttrDefinition::ATTR_MIN, Value::from(ValueData::IntDec(-1))),
+        (AttrDefinition::ATTR_MAX, Value::from(ValueData::IntDec(10))),
+    ]);
+    assert_eq!(bounded.format, AttrFormat::INTEGER);
+    assert_eq!((bounded.min, bounded.max), (Some(-1), Some(10)));
+    assert!(bounded.symbols.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_arrays_and_plurals() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("1.arsc"))?;
+    let package = &arsc.packages[0];
+    let ints = package.get_by_name("@array/ints2").unwrap();
+    let ints = &ints.entries[0].1.value;
+    assert_eq!(ints.as_array().map(|items| items.len()), Some(8));
+    assert!(ints.as_plurals().is_none());
+
+    let plurals = package.get_by_name("@plurals/items").unwrap();
+    let plurals = plurals.entries[0].1.value.as_plurals().unwrap();
+    assert_eq!(
+        plurals.keys().copied().collect::<Vec<_>>(),
+        vec![Quantity::Zero, Quantity::One, Quantity::Other]
+    );
+    assert!(plurals
+        .values()
+        .all(|value| matches!(value.data(), ValueData::Reference(_))));
+
+    let array = ResourceValue::array(vec![
+        Value::from(ValueData::IntDec(3)),
+        Value::from(ValueData::IntDec(1)),
+    ]);
+    let items = array.as_array().unwrap();
+    assert_eq!(
+        items.iter().map(|value| value.data()).collect::<Vec<_>>(),
+        vec![ValueData::IntDec(3), ValueData::IntDec(1)]
+    );
+    assert!(array.as_plurals().is_none());
+
+    let plurals = ResourceValue::plurals(vec![
+        (Quantity::Other, Value::from(ValueData::String(1))),
+        (Quantity::One, Value::from(ValueData::String(0))),
+    ]);
+    match &plurals {
+        ResourceValue::Bag { values, .. } => assert_eq!(
+            values.iter().map(|(key, _)| key.0).collect::<Vec<_>>(),
+            vec![0x01000006, 0x01000004]
+        ),
+        ResourceValue::Plain(_) => panic!("plurals is a bag"),
+    }
+    assert_eq!(
+        plurals.as_plurals().unwrap()[&Quantity::Other].data(),
+        ValueData::String(1)
+    );
+    assert!(ResourceValue::Plain(Value::from(ValueData::Null))
+        .as_array()
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn test_style_markup() -> Result<()> {
+    let arsc = parse(Path::new(SAMPLE_PATH).join("style_with_more_than_one_span.arsc"))?;
+    let pool = &arsc.global_string_pool;
+    assert_eq!(
+        pool.render_markup(0).as_deref(),
+        Some("<u>Czy potrzebujesz pomocy?</u>")
+    );
+    assert_eq!(
+        pool.render_markup(3).as_deref(),
+        Some("Grafiken von<a href=\"http://cookicons.co/\">CookIcons</a>")
+    );
+
+    // rendering then parsing gives the same string and spans
+    let mut copy = StringPool {
+        flags: pool.flags,
+        strings: Vec::new(),
+        styles: Vec::new(),
+    };
+    for index in 0..pool.styles.len() {
+        let markup = pool.render_markup(index).unwrap();
+        let copied = copy.push_markup(&markup).unwrap();
+        assert_eq!(copy.strings[copied], pool.strings[index]);
+        let spans = |pool: &StringPool, index: usize| {
+            pool.styles[index]
+                .spans
+                .iter()
+                .map(|span| {
+                    (
+                        pool.strings[span.name as usize].clone(),
+                        span.start,
+                        span.end,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&copy, copied), spans(pool, index));
+        assert_eq!(copy.render_markup(copied), Some(markup));
+    }
+
+    let mut pool = StringPool {
+        flags: 0,
+        strings: vec!["plain".to_string(), "b".to_string()],
+        styles: Vec::new(),
+    };
+    let index = pool
+        .push_markup("Hello <b>wo<font color=\"#ff0000\">rld</b> &amp; 😀</font>!")
+        .unwrap();
+    assert_eq!(pool.strings[index], "Hello world & 😀!");
+    assert_eq!(pool.styles.len(), index + 1);
+    assert!(pool.styles[0].spans.is_empty());
+    let spans = pool.styles[index]
+        .spans
+        .iter()
+        .map(|span| {
+            (
+                pool.strings[span.name as usize].as_str(),
+                span.start,
+                span.end,
+            )
+        })
+        .collect::<Vec<_>>();
+    // the emoji takes two UTF-16 code units
+    assert_eq!(spans, vec![("b", 6, 10), ("font;color=#ff0000", 8, 15)]);
+    assert_eq!(pool.strings.len(), 4);
+    assert_eq!(
+        pool.render_markup(index).as_deref(),
+        Some("Hello <b>wo<font color=\"#ff0000\">rld</font></b><font color=\"#ff0000\"> &amp; 😀</font>!")
+    );
+
+    pool.set_markup(0, "<i>plain</i>").unwrap();
+    assert_eq!(pool.render_markup(0).as_deref(), Some("<i>plain</i>"));
+    assert_eq!(
+        pool.set_markup(pool.strings.len(), "<i>missing</i>")
+            .map_err(|error| error.reason),
+        Err("string to replace does not exist")
+    );
+    // unstyled strings after the last style do not need empty styles
+    let styles = pool.styles.len();
+    let index = pool.push_markup("no spans").unwrap();
+    assert_eq!(pool.strings[index], "no spans");
+    assert_eq!(pool.styles.len(), styles);
+    // and the styles end with the last styled string
+    let styled = pool.push_markup("<b>styled</b>").unwrap();
+    assert_eq!(pool.styles.len(), styled + 1);
+    pool.set_markup(styled, "unstyled").unwrap();
+    assert_eq!(pool.styles.len(), styles);
+
+    // quoted `>` and empty spans survive parsing and rendering
+    let mut pool = StringPool {
+        flags: 0,
+        strings: Vec::new(),
+        styles: Vec::new(),
+    };
+    for markup in [
+        "<a href=\"https://example.com/?a>b\">link</a>",
+        "<i></i><b>ab</b>",
+        "<b>a<i></i>b</b>",
+        "ab<u></u>",
+        "<i></i><u></u>text",
+    ] {
+        let index = pool.push_markup(markup).unwrap();
+        assert_eq!(pool.render_markup(index).as_deref(), Some(markup));
+    }
+    let index = pool.push_markup("<b></b>").unwrap();
+    let span = &pool.styles[index].spans[0];
+    assert_eq!((span.start, span.end), (0, u32::MAX));
+    assert!(pool.push_markup("<b>unclosed").is_err());
+    assert!(pool.push_markup("closed</b>").is_err());
+    assert!(pool.push_markup("&unknown;").is_err());
+
+    // styled strings added to a parsed pool survive writing
+    let mut arsc = parse(Path::new(SAMPLE_PATH).join("basic.arsc"))?;
+    let index = arsc
+        .global_string_pool
+        .push_markup("<b>bold</b> move")
+        .unwrap();
+    let mut bytes = Vec::new();
+    write_to(&arsc, &mut bytes)?;
+    let arsc = parse_from(std::io::Cursor::new(bytes))?;
+    assert_eq!(
+        arsc.global_string_pool.render_markup(index).as_deref(),
+        Some("<b>bold</b> move")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_builder() -> Result<()> {
+    let default = ResTableConfig::default();
+    let fr = "fr".parse::<ResTableConfig>().unwrap();
+    let mut builder = ArscBuilder::new();
+    let package = builder.package(0x7f, "com.example.app");
+    let app_name = package.add_string("app_name", &default, "My App")?;
+    assert_eq!(app_name, ResourceId(0x7f010000));
+    assert_eq!(package.add_string("app_name", &fr, "Mon App")?, app_name);
+    let title = package.add_string("title", &default, "My App")?;
+    assert_eq!(title, ResourceId(0x7f010001));
+    let primary = package.add_color("primary", &default, 0xff2196f3)?;
+    assert_eq!(primary, ResourceId(0x7f020000));
+    let margin =
+        package.add_dimension("margin", &default, Dimension::new(16.0, DimensionUnit::Dp))?;
+    let numbers = ResourceValue::array(vec![
+        ValueData::IntDec(1).into(),
+        ValueData::IntDec(2).into(),
+    ]);
+    let numbers = package.add_bag("array", "numbers", &default, numbers)?;
+    let style = ResourceValue::Bag {
+        parent: ResourceId(0),
+        values: vec![(ResourceId(0x01010098), ValueData::Reference(primary).into())],
+    };
+    let style = package.add_bag("style", "AppTheme", &default, style)?;
+    assert_eq!(package.id_of("dimen", "margin"), Some(margin));
+    assert_eq!(package.id_of("dimen", "padding"), None);
+    // an entry that only exists in one config does not vary on anything
+    let only_fr = package.add_value("integer", "only_fr", &fr, ValueData::IntDec(1).into())?;
+    // string indices would point into a pool that is only built later
+    assert!(matches!(
+        package.add_value("integer", "index", &default, ValueData::String(0).into()),
+        Err(Error::BadHeader { .. })
+    ));
+    let strings = ResourceValue::array(vec![ValueData::String(0).into()]);
+    assert!(matches!(
+        package.add_bag("array", "strings", &default, strings),
+        Err(Error::BadHeader { .. })
+    ));
+    assert_eq!(package.id_of("array", "strings"), None);
+
+    // ids that do not fit in a resource id are rejected instead of wrapping
+    let mut limits = ArscBuilder::new();
+    let limits = limits.package(0x7f, "com.example.limits");
+    for entry in 0..=u16::MAX as usize {
+        limits.add_value(
+            "integer",
+            &entry.to_string(),
+            &default,
+            ValueData::IntDec(0).into(),
+        )?;
+    }
+    // existing entries can still get values
+    let existing = limits.add_value("integer", "0", &default, ValueData::IntDec(1).into())?;
+    assert_eq!(existing, ResourceId(0x7f010000));
+    assert!(matches!(
+        limits.add_value("integer", "65536", &default, ValueData::IntDec(0).into()),
+        Err(Error::Overflow { target: "u16", .. })
+    ));
+    for r#type in 1..u8::MAX {
+        limits.add_value(
+            &r#type.to_string(),
+            "value",
+            &default,
+            ValueData::IntDec(0).into(),
+        )?;
+    }
+    assert!(matches!(
+        limits.add_value(
+            "one_too_many",
+            "value",
+            &default,
+            ValueData::IntDec(0).into()
+        ),
+        Err(Error::Overflow { target: "u8", .. })
+    ));
+
+    let mut bytes = vec![];
+    write_to(&builder.build(), &mut bytes)?;
+    let arsc = parse_from(std::io::Cursor::new(&bytes))?;
+    let view = arsc.get_by_name("@string/app_name").unwrap();
+    assert_eq!(view.id, app_name);
+    assert_eq!(view.spec_flags, Some(ResTableConfig::CONFIG_LOCALE));
+    assert_eq!(arsc.get(title).unwrap().spec_flags, Some(0));
+    assert_eq!(arsc.get(only_fr).unwrap().spec_flags, Some(0));
+    let string_of = |device: &str| {
+        let device = device.parse::<DeviceConfig>().unwrap();
+        match &arsc.resolve(app_name, &device).unwrap().value {
+            ResourceValue::Plain(value) => match value.data() {
+                ValueData::String(index) => arsc.global_string_pool.strings[index].clone(),
+                data => panic!("unexpected {:?}", data),
+            },
+            value => panic!("unexpected {:?}", value),
+        }
+    };
+    assert_eq!(string_of("fr-rFR"), "Mon App");
+    assert_eq!(string_of("en-rUS"), "My App");
+    // equal strings share an index in the global string pool
+    assert_eq!(arsc.global_string_pool.strings.len(), 2);
+
+    let device = DeviceConfig::default();
+    let resolved = arsc.resolve_reference(primary, &device).unwrap();
+    assert_eq!(resolved.value.data(), ValueData::ColorArgb8(0xff2196f3));
+    let resolved = arsc.resolve_reference(margin, &device).unwrap();
+    assert_eq!(
+        resolved.value.data().dimension().unwrap().to_string(),
+        "16.0dp"
+    );
+    let numbers = arsc.resolve(numbers, &device).unwrap();
+    let numbers = numbers.value.as_array().unwrap();
+    assert_eq!(numbers.len(), 2);
+    assert_eq!(numbers[1].data(), ValueData::IntDec(2));
+    let style = arsc.resolve_style(style, &device).unwrap();
+    assert_eq!(style.attributes.len(), 1);
+
+    // the built table survives a round trip unchanged
+    let mut rewritten = vec![];
+    write_to(&arsc, &mut rewritten)?;
+    assert_eq!(bytes, rewritten);
+    Ok(())
+}